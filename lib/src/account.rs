@@ -1,4 +1,4 @@
-use reth_primitives::{Address, Bytes};
+use reth_primitives::{Address, Bytes, B256, U256};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
@@ -6,6 +6,33 @@ pub struct HdpAccount {
     pub address: Address,
     pub account_key: String,
     pub proofs: Vec<ProcessedMPTProof>,
+    /// Values reported by the RPC alongside the proofs; unverified until checked against them.
+    #[serde(default)]
+    pub claims: Vec<AccountClaim>,
+}
+
+impl HdpAccount {
+    /// The account proof for `block_number`, if one was supplied.
+    pub fn proof_at(&self, block_number: u64) -> Option<&[Bytes]> {
+        proof_at(&self.proofs, block_number)
+    }
+
+    /// The claimed account fields at `block_number`, if any.
+    pub fn claim_at(&self, block_number: u64) -> Option<&AccountClaim> {
+        self.claims
+            .iter()
+            .find(|claim| claim.block_number == block_number)
+    }
+}
+
+/// Account fields of an `eth_getProof` response at `block_number`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct AccountClaim {
+    pub block_number: u64,
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_root: B256,
+    pub code_hash: B256,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
@@ -13,3 +40,10 @@ pub struct ProcessedMPTProof {
     pub block_number: u64,
     pub proof: Vec<Bytes>,
 }
+
+pub(crate) fn proof_at(proofs: &[ProcessedMPTProof], block_number: u64) -> Option<&[Bytes]> {
+    proofs
+        .iter()
+        .find(|proof| proof.block_number == block_number)
+        .map(|proof| proof.proof.as_slice())
+}
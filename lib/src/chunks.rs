@@ -0,0 +1,169 @@
+use alloy_rlp::Header as RlpHeader;
+use starknet_types_core::felt::Felt;
+
+/// Number of bytes packed into a single felt word.
+pub const WORD_SIZE: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChunkError {
+    #[error("felt word {0} does not fit in u64")]
+    WordOverflow(usize),
+
+    #[error("expected {expected} felt words for {bytes_len} bytes, got {got}")]
+    WordCountMismatch {
+        bytes_len: usize,
+        expected: usize,
+        got: usize,
+    },
+
+    #[error("non-zero bytes past the end of the final word")]
+    NonZeroPadding,
+
+    #[error("invalid rlp: {0}")]
+    Rlp(#[from] alloy_rlp::Error),
+}
+
+/// Packs bytes into little-endian u64 words, the layout the Cairo verifier uses for RLP.
+/// The final word holds the remaining `len % 8` bytes, if any.
+pub fn bytes_to_felts(bytes: &[u8]) -> Vec<Felt> {
+    bytes
+        .chunks(WORD_SIZE)
+        .map(|chunk| {
+            let mut word = [0u8; WORD_SIZE];
+            word[..chunk.len()].copy_from_slice(chunk);
+            Felt::from(u64::from_le_bytes(word))
+        })
+        .collect()
+}
+
+/// Unpacks little-endian u64 words back into exactly `bytes_len` bytes.
+pub fn felts_to_bytes(felts: &[Felt], bytes_len: usize) -> Result<Vec<u8>, ChunkError> {
+    let expected = bytes_len.div_ceil(WORD_SIZE);
+    if felts.len() != expected {
+        return Err(ChunkError::WordCountMismatch {
+            bytes_len,
+            expected,
+            got: felts.len(),
+        });
+    }
+
+    strip_padding(unpack_words(felts)?, bytes_len)
+}
+
+/// Unpacks an RLP item from felt words, using its own RLP header to recover the byte length.
+pub fn felts_to_rlp(felts: &[Felt]) -> Result<Vec<u8>, ChunkError> {
+    let bytes = unpack_words(felts)?;
+
+    let mut buf = bytes.as_slice();
+    let header = RlpHeader::decode(&mut buf)?;
+    // the words end where the item does, so a longer payload cannot be in them
+    if header.payload_length > buf.len() {
+        return Err(alloy_rlp::Error::InputTooShort.into());
+    }
+    let bytes_len = bytes.len() - buf.len() + header.payload_length;
+
+    let expected = bytes_len.div_ceil(WORD_SIZE);
+    if felts.len() != expected {
+        return Err(ChunkError::WordCountMismatch {
+            bytes_len,
+            expected,
+            got: felts.len(),
+        });
+    }
+
+    strip_padding(bytes, bytes_len)
+}
+
+fn unpack_words(felts: &[Felt]) -> Result<Vec<u8>, ChunkError> {
+    let mut bytes = Vec::with_capacity(felts.len() * WORD_SIZE);
+    for (i, felt) in felts.iter().enumerate() {
+        let digits = felt.to_le_digits();
+        if digits[1..].iter().any(|digit| *digit != 0) {
+            return Err(ChunkError::WordOverflow(i));
+        }
+        bytes.extend_from_slice(&digits[0].to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+fn strip_padding(mut bytes: Vec<u8>, bytes_len: usize) -> Result<Vec<u8>, ChunkError> {
+    if bytes[bytes_len..].iter().any(|byte| *byte != 0) {
+        return Err(ChunkError::NonZeroPadding);
+    }
+    bytes.truncate(bytes_len);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_rlp::Decodable;
+    use reth_primitives::{hex, Header};
+
+    use super::*;
+
+    #[test]
+    fn test_partial_final_word() {
+        let bytes = hex!("f90264a0d0dbb039df7728");
+        let felts = bytes_to_felts(&bytes);
+
+        assert_eq!(
+            felts,
+            vec![
+                Felt::from_hex_unchecked("0x39b0dbd0a06402f9"),
+                Felt::from_hex_unchecked("0x2877df"),
+            ]
+        );
+        assert_eq!(felts_to_bytes(&felts, bytes.len()).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_trailing_zero_bytes_roundtrip() {
+        let bytes = hex!("0102030405060708090000");
+        let felts = bytes_to_felts(&bytes);
+
+        assert_eq!(felts.len(), 2);
+        assert_eq!(felts_to_bytes(&felts, bytes.len()).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_words() {
+        let overflow = vec![Felt::from(u128::from(u64::MAX) + 1)];
+        assert!(matches!(
+            felts_to_bytes(&overflow, 8),
+            Err(ChunkError::WordOverflow(0))
+        ));
+
+        let padded = vec![Felt::from(0x0100_u64)];
+        assert!(matches!(
+            felts_to_bytes(&padded, 1),
+            Err(ChunkError::NonZeroPadding)
+        ));
+
+        assert!(matches!(
+            felts_to_bytes(&padded, 9),
+            Err(ChunkError::WordCountMismatch { expected: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_rlp_longer_than_words() {
+        // a list header claiming a payload of u64::MAX bytes
+        let felts = bytes_to_felts(&hex!("ffffffffffffffffff"));
+        assert!(matches!(
+            felts_to_rlp(&felts),
+            Err(ChunkError::Rlp(alloy_rlp::Error::InputTooShort))
+        ));
+    }
+
+    #[test]
+    fn test_header_rlp_roundtrip() {
+        let rlp = hex!("f901fda025a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942f14582947e292a2ecd20c430b46f2d27cfe213ca0c91d4ecd59dce3067d340b3aadfc0542974b4fb4db98af39f980a91ea00db9dca056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018401c9c38080846173603a80a0cd039d5508e92723db0f078b5205da89144e3a6fee3a34124c966f53c35ce42c88c7faaf72b456848084342770c0");
+        let felts = bytes_to_felts(&rlp);
+
+        assert_eq!(felts.len(), rlp.len().div_ceil(WORD_SIZE));
+        assert_eq!(felts_to_rlp(&felts).unwrap(), rlp);
+
+        let decoded = Header::decode(&mut felts_to_rlp(&felts).unwrap().as_slice()).unwrap();
+        assert_eq!(decoded.number, 1);
+    }
+}
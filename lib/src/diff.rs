@@ -0,0 +1,454 @@
+use alloy_primitives::{Address, B256, U256};
+use reth_primitives::{Header, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::HdpAccount,
+    mpt::{verify_account_proof, verify_storage_proof, MptError},
+    storage::HdpStorage,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error("diff is for block {expected}, header is block {got}")]
+    BlockNumberMismatch { expected: u64, got: u64 },
+
+    #[error("no verified header for block {0}")]
+    MissingHeader(u64),
+}
+
+/// An account and the slots to compare between blocks `from_block` and `to_block`.
+/// Every proof must be supplied for both blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct HdpDiff {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub account: HdpAccount,
+    pub storages: Vec<HdpStorage>,
+}
+
+/// A value at the earlier and the later block.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    pub fn is_changed(&self) -> bool {
+        self.old != self.new
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct SlotDiff {
+    pub slot: B256,
+    pub value: Change<U256>,
+}
+
+/// Proven changes of an account between two blocks. An account missing from the state
+/// is reported with `exists == false` and the fields of an empty account.
+/// The guest commits the number and hash of both blocks with it, see
+/// [`crate::verify::PublicValues`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct AccountDiff {
+    pub address: Address,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub exists: Change<bool>,
+    pub nonce: Change<u64>,
+    pub balance: Change<U256>,
+    pub storage_root: Change<B256>,
+    pub code_hash: Change<B256>,
+    /// Every requested slot, changed or not.
+    pub storage: Vec<SlotDiff>,
+}
+
+impl AccountDiff {
+    /// Whether nothing in the account or the requested slots changed.
+    pub fn is_unchanged(&self) -> bool {
+        !(self.exists.is_changed()
+            || self.nonce.is_changed()
+            || self.balance.is_changed()
+            || self.storage_root.is_changed()
+            || self.code_hash.is_changed()
+            || self.storage.iter().any(|slot| slot.value.is_changed()))
+    }
+}
+
+struct Snapshot {
+    exists: bool,
+    nonce: u64,
+    balance: U256,
+    storage_root: B256,
+    code_hash: B256,
+    slots: Vec<U256>,
+}
+
+/// Verifies the proofs of `diff` against the state roots of two verified headers and
+/// returns the changes between them.
+pub fn diff_account(from: &Header, to: &Header, diff: &HdpDiff) -> Result<AccountDiff, DiffError> {
+    for (expected, header) in [(diff.from_block, from), (diff.to_block, to)] {
+        if header.number != expected {
+            return Err(DiffError::BlockNumberMismatch {
+                expected,
+                got: header.number,
+            });
+        }
+    }
+
+    let old = snapshot(from, diff)?;
+    let new = snapshot(to, diff)?;
+
+    Ok(AccountDiff {
+        address: diff.account.address,
+        from_block: from.number,
+        to_block: to.number,
+        exists: Change {
+            old: old.exists,
+            new: new.exists,
+        },
+        nonce: Change {
+            old: old.nonce,
+            new: new.nonce,
+        },
+        balance: Change {
+            old: old.balance,
+            new: new.balance,
+        },
+        storage_root: Change {
+            old: old.storage_root,
+            new: new.storage_root,
+        },
+        code_hash: Change {
+            old: old.code_hash,
+            new: new.code_hash,
+        },
+        storage: diff
+            .storages
+            .iter()
+            .zip(old.slots.into_iter().zip(new.slots))
+            .map(|(storage, (old, new))| SlotDiff {
+                slot: storage.slot,
+                value: Change { old, new },
+            })
+            .collect(),
+    })
+}
+
+/// Like [`diff_account`], picking both headers of `diff` out of `headers`.
+pub fn diff_account_in(headers: &[Header], diff: &HdpDiff) -> Result<AccountDiff, DiffError> {
+    let find = |number| {
+        headers
+            .iter()
+            .find(|header| header.number == number)
+            .ok_or(DiffError::MissingHeader(number))
+    };
+    diff_account(find(diff.from_block)?, find(diff.to_block)?, diff)
+}
+
+fn snapshot(header: &Header, diff: &HdpDiff) -> Result<Snapshot, MptError> {
+    let block_number = header.number;
+    let account_proof = diff
+        .account
+        .proof_at(block_number)
+        .ok_or(MptError::MissingBlockProof(block_number))?;
+    let account = verify_account_proof(diff.account.address, header.state_root, account_proof)?;
+
+    let mut snapshot = match account {
+        Some(account) => Snapshot {
+            exists: true,
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root: account.storage_root,
+            code_hash: account.code_hash,
+            slots: Vec::with_capacity(diff.storages.len()),
+        },
+        None => Snapshot {
+            exists: false,
+            nonce: 0,
+            balance: U256::ZERO,
+            storage_root: EMPTY_ROOT_HASH,
+            code_hash: KECCAK_EMPTY,
+            slots: Vec::with_capacity(diff.storages.len()),
+        },
+    };
+
+    for storage in &diff.storages {
+        if storage.address != diff.account.address {
+            return Err(MptError::AddressMismatch {
+                account: diff.account.address,
+                storage: storage.address,
+            });
+        }
+        let proof = storage
+            .proof_at(block_number)
+            .ok_or(MptError::MissingBlockProof(block_number))?;
+        snapshot.slots.push(verify_storage_proof(
+            snapshot.storage_root,
+            storage.slot,
+            proof,
+        )?);
+    }
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{keccak256, Bytes};
+    use reth_primitives::{address, b256, hex};
+
+    use super::*;
+    use crate::account::ProcessedMPTProof;
+
+    fn slot(n: u64) -> B256 {
+        B256::from(U256::from(n))
+    }
+
+    fn headers() -> (Header, Header) {
+        (
+            Header {
+                number: FROM,
+                state_root: FROM_STATE_ROOT,
+                ..Default::default()
+            },
+            Header {
+                number: TO,
+                state_root: TO_STATE_ROOT,
+                ..Default::default()
+            },
+        )
+    }
+
+    // the same two accounts in the state tries of two blocks
+    const FROM: u64 = 100;
+    const TO: u64 = 200;
+    const FROM_STATE_ROOT: B256 =
+        b256!("c108e4abce9dae6dfefee18c540a07dcda4e7c9dc441a53d20c4d54e3e32daf6");
+    const TO_STATE_ROOT: B256 =
+        b256!("720cace1205f78b5ae47f36d033cb3423954a03e94e185fa57b3428233c10c5f");
+
+    /// Account 0x11.. with slots 0, 1 and 2 at both blocks.
+    fn updated() -> (HdpAccount, Vec<HdpStorage>) {
+        let address = address!("1111111111111111111111111111111111111111");
+        let account = HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![
+                ProcessedMPTProof {
+                    block_number: FROM,
+                    proof: vec![
+                        Bytes::from(hex!("f851808080a07c8d572913531a30951c50b0da90a4c0ed75af94f3552a903b0bfe3826f6579c80808080808080808080a0bcbfe1cd1d8233eb09fb4ca32b456d7902542f3ee816229e5bddbad8179671198080")),
+                        Bytes::from(hex!("f869a032c07404b8c1df4c46226425cac68c28d27a766bbddce62309f36724839b22c0b846f844010aa0a5bc992e3fe5766875e7f813897ca8f10bdd0085fd8dd4fac8f1c426f22aaff3a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                    ],
+                },
+                ProcessedMPTProof {
+                    block_number: TO,
+                    proof: vec![
+                        Bytes::from(hex!("f8718080a093b21c92a5a59e252ae69389abf9f6a8eb291f8058014a482107d4f5cbcc9b0ba07c8d572913531a30951c50b0da90a4c0ed75af94f3552a903b0bfe3826f6579c80808080808080808080a0d9792b8b2b06e32aeb8845f8e3a89607fbdb0d02d6bf3f861e3b6e693419a3f58080")),
+                        Bytes::from(hex!("f869a032c07404b8c1df4c46226425cac68c28d27a766bbddce62309f36724839b22c0b846f8440207a0e3060347f5a7007cb49b612c3ec2e271c3afef252a7d41a91e815a0ec5f8c887a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                    ],
+                },
+            ],
+            claims: vec![],
+        };
+        let storages = vec![
+            HdpStorage {
+                address,
+                slot: slot(0),
+                storage_key: keccak256(slot(0)),
+                proofs: vec![
+                    ProcessedMPTProof {
+                        block_number: FROM,
+                        proof: vec![
+                            Bytes::from(hex!("f8518080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb288080808080808080a07d17f886e910f7b02b0821a7a032f8f7427a982d135a10f695283157ac0068918080808080")),
+                            Bytes::from(hex!("e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e56301")),
+                        ],
+                    },
+                    ProcessedMPTProof {
+                        block_number: TO,
+                        proof: vec![
+                            Bytes::from(hex!("f8718080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb2880a0cd457259696115235e64c7822334d62129e2f1604425a7da6494f35fc45be518808080808080a07b58866e3801680bea90c82a80eb08889ececef107b8b504ae1d1a1e1e17b7af8080808080")),
+                            Bytes::from(hex!("e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e56301")),
+                        ],
+                    },
+                ],
+                claims: vec![],
+            },
+            HdpStorage {
+                address,
+                slot: slot(1),
+                storage_key: keccak256(slot(1)),
+                proofs: vec![
+                    ProcessedMPTProof {
+                        block_number: FROM,
+                        proof: vec![
+                            Bytes::from(hex!("f8518080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb288080808080808080a07d17f886e910f7b02b0821a7a032f8f7427a982d135a10f695283157ac0068918080808080")),
+                            Bytes::from(hex!("e2a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf605")),
+                        ],
+                    },
+                    ProcessedMPTProof {
+                        block_number: TO,
+                        proof: vec![
+                            Bytes::from(hex!("f8718080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb2880a0cd457259696115235e64c7822334d62129e2f1604425a7da6494f35fc45be518808080808080a07b58866e3801680bea90c82a80eb08889ececef107b8b504ae1d1a1e1e17b7af8080808080")),
+                            Bytes::from(hex!("e2a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf609")),
+                        ],
+                    },
+                ],
+                claims: vec![],
+            },
+            HdpStorage {
+                address,
+                slot: slot(2),
+                storage_key: keccak256(slot(2)),
+                proofs: vec![
+                    ProcessedMPTProof {
+                        block_number: FROM,
+                        proof: vec![
+                            Bytes::from(hex!("f8518080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb288080808080808080a07d17f886e910f7b02b0821a7a032f8f7427a982d135a10f695283157ac0068918080808080")),
+                        ],
+                    },
+                    ProcessedMPTProof {
+                        block_number: TO,
+                        proof: vec![
+                            Bytes::from(hex!("f8718080a04fc5f13ab2f9ba0c2da88b0151ab0e7cf4d85d08cca45ccd923c6ab76323eb2880a0cd457259696115235e64c7822334d62129e2f1604425a7da6494f35fc45be518808080808080a07b58866e3801680bea90c82a80eb08889ececef107b8b504ae1d1a1e1e17b7af8080808080")),
+                            Bytes::from(hex!("e2a0305787fa12a823e0f2b7631cc41b3ba8828b3321ca811111fa75cd3aa3bb5ace03")),
+                        ],
+                    },
+                ],
+                claims: vec![],
+            },
+        ];
+        (account, storages)
+    }
+
+    /// Account 0x22.., created between the two blocks.
+    fn created() -> HdpAccount {
+        let address = address!("2222222222222222222222222222222222222222");
+        HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![
+                ProcessedMPTProof {
+                    block_number: FROM,
+                    proof: vec![
+                        Bytes::from(hex!("f851808080a07c8d572913531a30951c50b0da90a4c0ed75af94f3552a903b0bfe3826f6579c80808080808080808080a0bcbfe1cd1d8233eb09fb4ca32b456d7902542f3ee816229e5bddbad8179671198080")),
+                    ],
+                },
+                ProcessedMPTProof {
+                    block_number: TO,
+                    proof: vec![
+                        Bytes::from(hex!("f8718080a093b21c92a5a59e252ae69389abf9f6a8eb291f8058014a482107d4f5cbcc9b0ba07c8d572913531a30951c50b0da90a4c0ed75af94f3552a903b0bfe3826f6579c80808080808080808080a0d9792b8b2b06e32aeb8845f8e3a89607fbdb0d02d6bf3f861e3b6e693419a3f58080")),
+                        Bytes::from(hex!("f869a03ab0a4443bbea3fbe4d0e1503d11ff1367842fb0c8b28a5c8550f27599a40751b846f8448064a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                    ],
+                },
+            ],
+            claims: vec![],
+        }
+    }
+
+    #[test]
+    fn test_updated_account() {
+        let (from, to) = headers();
+        let (account, storages) = updated();
+        let diff = HdpDiff {
+            from_block: FROM,
+            to_block: TO,
+            account,
+            storages,
+        };
+
+        let proven = diff_account(&from, &to, &diff).unwrap();
+        assert!(!proven.is_unchanged());
+        assert!(!proven.exists.is_changed());
+        assert_eq!(proven.nonce, Change { old: 1, new: 2 });
+        assert_eq!(
+            proven.balance,
+            Change {
+                old: U256::from(10),
+                new: U256::from(7)
+            }
+        );
+        assert!(proven.storage_root.is_changed());
+        assert!(!proven.code_hash.is_changed());
+
+        let values: Vec<_> = proven
+            .storage
+            .iter()
+            .map(|slot| (slot.value.old, slot.value.new))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (U256::from(1), U256::from(1)),
+                (U256::from(5), U256::from(9)),
+                (U256::ZERO, U256::from(3)),
+            ]
+        );
+
+        assert!(matches!(
+            diff_account(&to, &from, &diff),
+            Err(DiffError::BlockNumberMismatch {
+                expected: FROM,
+                got: TO
+            })
+        ));
+    }
+
+    #[test]
+    fn test_created_account() {
+        let (from, to) = headers();
+        let diff = HdpDiff {
+            from_block: FROM,
+            to_block: TO,
+            account: created(),
+            storages: vec![],
+        };
+
+        let proven = diff_account(&from, &to, &diff).unwrap();
+        assert_eq!(
+            proven.exists,
+            Change {
+                old: false,
+                new: true
+            }
+        );
+        assert_eq!(proven.balance.new, U256::from(100));
+        assert_eq!(proven.storage_root.old, EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn test_missing_header_and_proof() {
+        let (from, to) = headers();
+        let (account, storages) = updated();
+        let diff = HdpDiff {
+            from_block: FROM,
+            to_block: TO,
+            account,
+            storages,
+        };
+
+        assert!(diff_account_in(&[from.clone(), to.clone()], &diff).is_ok());
+        assert!(matches!(
+            diff_account_in(&[to.clone()], &diff),
+            Err(DiffError::MissingHeader(FROM))
+        ));
+
+        let later = Header {
+            number: TO + 1,
+            state_root: TO_STATE_ROOT,
+            ..Default::default()
+        };
+        let diff = HdpDiff {
+            to_block: TO + 1,
+            ..diff
+        };
+        assert!(matches!(
+            diff_account(&from, &later, &diff),
+            Err(DiffError::Mpt(MptError::MissingBlockProof(201)))
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+use alloy_primitives::{hex, keccak256, Bytes, B256, U256};
+use alloy_rpc_types_eth::{EIP1186AccountProofResponse, EIP1186StorageProof};
+use reth_primitives::{Account as RethAccount, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+use reth_trie::{AccountProof, StorageProof};
+
+use crate::{
+    account::{AccountClaim, HdpAccount, ProcessedMPTProof},
+    mpt::{verify_account_proof, verify_storage, MptError},
+    storage::{HdpStorage, StorageClaim},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Eip1186Error {
+    #[error("no eth_getProof responses")]
+    NoResponses,
+
+    #[error("response at block {0} is for a different account")]
+    AddressMismatch(u64),
+
+    #[error("response at block {0} proves a different set of slots")]
+    SlotMismatch(u64),
+
+    #[error("no claim for block {0}")]
+    MissingClaim(u64),
+
+    #[error("claimed account at block {0} does not match the proof")]
+    AccountClaimMismatch(u64),
+
+    #[error("claimed value of slot {slot} at block {block_number} does not match the proof")]
+    StorageClaimMismatch { block_number: u64, slot: B256 },
+
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+}
+
+/// An `eth_getProof` response together with the block it was requested at,
+/// which the response itself does not carry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockProofResponse {
+    pub block_number: u64,
+    pub response: EIP1186AccountProofResponse,
+}
+
+impl From<BlockProofResponse> for HdpAccount {
+    fn from(value: BlockProofResponse) -> Self {
+        let BlockProofResponse {
+            block_number,
+            response,
+        } = value;
+        Self {
+            address: response.address,
+            account_key: hex::encode(keccak256(response.address)),
+            proofs: vec![ProcessedMPTProof {
+                block_number,
+                proof: response.account_proof,
+            }],
+            claims: vec![AccountClaim {
+                block_number,
+                nonce: response.nonce,
+                balance: response.balance,
+                storage_root: response.storage_hash,
+                code_hash: response.code_hash,
+            }],
+        }
+    }
+}
+
+impl From<BlockProofResponse> for Vec<HdpStorage> {
+    fn from(value: BlockProofResponse) -> Self {
+        let BlockProofResponse {
+            block_number,
+            response,
+        } = value;
+        response
+            .storage_proof
+            .into_iter()
+            .map(|EIP1186StorageProof { key, value, proof }| HdpStorage {
+                address: response.address,
+                slot: key.0,
+                storage_key: keccak256(key.0),
+                proofs: vec![ProcessedMPTProof {
+                    block_number,
+                    proof,
+                }],
+                claims: vec![StorageClaim {
+                    block_number,
+                    value,
+                }],
+            })
+            .collect()
+    }
+}
+
+/// Merges `eth_getProof` responses for the same account and slots at several blocks into
+/// one account request and one storage request per slot.
+pub fn from_responses(
+    responses: impl IntoIterator<Item = BlockProofResponse>,
+) -> Result<(HdpAccount, Vec<HdpStorage>), Eip1186Error> {
+    let mut responses = responses.into_iter();
+    let first = responses.next().ok_or(Eip1186Error::NoResponses)?;
+    let mut account = HdpAccount::from(first.clone());
+    let mut storages = Vec::<HdpStorage>::from(first);
+
+    for response in responses {
+        let block_number = response.block_number;
+        if response.response.address != account.address {
+            return Err(Eip1186Error::AddressMismatch(block_number));
+        }
+        let next_storages = Vec::<HdpStorage>::from(response.clone());
+        if next_storages.len() != storages.len()
+            || next_storages
+                .iter()
+                .zip(&storages)
+                .any(|(next, storage)| next.slot != storage.slot)
+        {
+            return Err(Eip1186Error::SlotMismatch(block_number));
+        }
+
+        let next_account = HdpAccount::from(response);
+        account.proofs.extend(next_account.proofs);
+        account.claims.extend(next_account.claims);
+        for (storage, next) in storages.iter_mut().zip(next_storages) {
+            storage.proofs.extend(next.proofs);
+            storage.claims.extend(next.claims);
+        }
+    }
+    Ok((account, storages))
+}
+
+/// Checks the claimed account fields and slot values at `block_number` against the proofs.
+/// A claim of an empty account matches an account missing from the state.
+pub fn verify_claims(
+    state_root: B256,
+    block_number: u64,
+    account: &HdpAccount,
+    storages: &[HdpStorage],
+) -> Result<(), Eip1186Error> {
+    let claim = account
+        .claim_at(block_number)
+        .ok_or(Eip1186Error::MissingClaim(block_number))?;
+    let proof = account
+        .proof_at(block_number)
+        .ok_or(MptError::MissingBlockProof(block_number))?;
+    let matches = match verify_account_proof(account.address, state_root, proof)? {
+        Some(proven) => {
+            proven.nonce == claim.nonce
+                && proven.balance == claim.balance
+                && proven.storage_root == claim.storage_root
+                && proven.code_hash == claim.code_hash
+        }
+        None => is_missing(claim),
+    };
+    if !matches {
+        return Err(Eip1186Error::AccountClaimMismatch(block_number));
+    }
+
+    let slots = verify_storage(state_root, block_number, account, storages)?;
+    for storage in storages {
+        let claim = storage
+            .claim_at(block_number)
+            .ok_or(Eip1186Error::MissingClaim(block_number))?;
+        if slots[&storage.slot] != claim.value {
+            return Err(Eip1186Error::StorageClaimMismatch {
+                block_number,
+                slot: storage.slot,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// One reth `StorageProof` per block, with the claimed value where there is one.
+impl From<&HdpStorage> for Vec<StorageProof> {
+    fn from(value: &HdpStorage) -> Self {
+        value
+            .proofs
+            .iter()
+            .map(|proof| storage_proof(value, proof.block_number, &proof.proof))
+            .collect()
+    }
+}
+
+/// One reth `AccountProof` per block of `account`, carrying the claimed account and the
+/// storage proofs of `storages` for the same block.
+pub fn into_account_proofs(account: &HdpAccount, storages: &[HdpStorage]) -> Vec<AccountProof> {
+    account
+        .proofs
+        .iter()
+        .map(|proof| {
+            let mut account_proof = AccountProof::new(account.address);
+            account_proof.proof = proof.proof.clone();
+            if let Some(claim) = account.claim_at(proof.block_number) {
+                account_proof.info = (!is_missing(claim)).then(|| RethAccount {
+                    nonce: claim.nonce,
+                    balance: claim.balance,
+                    bytecode_hash: (claim.code_hash != KECCAK_EMPTY).then_some(claim.code_hash),
+                });
+                if claim.storage_root != B256::ZERO {
+                    account_proof.storage_root = claim.storage_root;
+                }
+            }
+            account_proof.storage_proofs = storages
+                .iter()
+                .filter(|storage| storage.address == account.address)
+                .filter_map(|storage| {
+                    let block_proof = storage.proof_at(proof.block_number)?;
+                    Some(storage_proof(storage, proof.block_number, block_proof))
+                })
+                .collect();
+            account_proof
+        })
+        .collect()
+}
+
+fn storage_proof(storage: &HdpStorage, block_number: u64, proof: &[Bytes]) -> StorageProof {
+    let mut storage_proof =
+        StorageProof::new_with_hashed(storage.slot, storage.storage_key).with_proof(proof.to_vec());
+    if let Some(claim) = storage.claim_at(block_number) {
+        storage_proof.value = claim.value;
+    }
+    storage_proof
+}
+
+/// Nodes report missing accounts with zero fields, and either zero or empty-trie hashes.
+fn is_missing(claim: &AccountClaim) -> bool {
+    claim.nonce == 0
+        && claim.balance == U256::ZERO
+        && (claim.code_hash == B256::ZERO || claim.code_hash == KECCAK_EMPTY)
+        && (claim.storage_root == B256::ZERO || claim.storage_root == EMPTY_ROOT_HASH)
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, hex};
+
+    use super::*;
+
+    fn response(block_number: u64, slots: &[(u64, u64)]) -> BlockProofResponse {
+        BlockProofResponse {
+            block_number,
+            response: EIP1186AccountProofResponse {
+                address: address!("75cec1db9dceb703200eaa6595f66885c962b920"),
+                nonce: block_number,
+                code_hash: KECCAK_EMPTY,
+                storage_hash: EMPTY_ROOT_HASH,
+                account_proof: vec![Bytes::from(hex!("c0"))],
+                storage_proof: slots
+                    .iter()
+                    .map(|(slot, value)| EIP1186StorageProof {
+                        key: B256::from(U256::from(*slot)).into(),
+                        value: U256::from(*value),
+                        proof: vec![Bytes::from(vec![*slot as u8])],
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_from_responses() {
+        let (account, storages) = from_responses([
+            response(1, &[(0, 10), (1, 11)]),
+            response(2, &[(0, 20), (1, 21)]),
+        ])
+        .unwrap();
+
+        assert_eq!(account.proofs.len(), 2);
+        assert_eq!(account.claim_at(2).unwrap().nonce, 2);
+        assert_eq!(storages.len(), 2);
+        assert_eq!(storages[1].slot, B256::from(U256::from(1)));
+        assert_eq!(storages[1].storage_key, keccak256(storages[1].slot));
+        assert_eq!(storages[1].claim_at(2).unwrap().value, U256::from(21));
+
+        let proofs = into_account_proofs(&account, &storages);
+        assert_eq!(proofs.len(), 2);
+        assert_eq!(proofs[0].info.unwrap().nonce, 1);
+        assert_eq!(proofs[1].storage_proofs[0].value, U256::from(20));
+        assert_eq!(
+            proofs[1].storage_proofs[1].proof,
+            vec![Bytes::from(vec![1])]
+        );
+
+        let storage_proofs = Vec::<StorageProof>::from(&storages[0]);
+        assert_eq!(storage_proofs[0].key, storages[0].slot);
+        assert_eq!(storage_proofs[0].value, U256::from(10));
+
+        assert!(matches!(
+            from_responses([response(1, &[(0, 10)]), response(2, &[(1, 21)])]),
+            Err(Eip1186Error::SlotMismatch(2))
+        ));
+        assert!(matches!(
+            from_responses(Vec::new()),
+            Err(Eip1186Error::NoResponses)
+        ));
+    }
+
+    #[test]
+    fn test_missing_account() {
+        let mut missing = response(1, &[]);
+        missing.response.nonce = 0;
+        missing.response.code_hash = B256::ZERO;
+        missing.response.storage_hash = B256::ZERO;
+
+        let account = HdpAccount::from(missing);
+        let proof = &into_account_proofs(&account, &[])[0];
+        assert_eq!(proof.info, None);
+        assert_eq!(proof.storage_root, EMPTY_ROOT_HASH);
+    }
+}
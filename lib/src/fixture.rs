@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use alloy_primitives::{hex, keccak256, Address, Bytes, B256, U256};
+use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles};
+use reth_primitives::{Header, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+
+use crate::{
+    account::{AccountClaim, HdpAccount, ProcessedMPTProof},
+    rlp::Account,
+    storage::{HdpStorage, StorageClaim},
+};
+
+/// An account to place in the state built by [`StateBuilder`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixtureAccount {
+    pub nonce: u64,
+    pub balance: U256,
+    pub code: Bytes,
+    /// Slots holding zero are left out of the storage trie, as in Ethereum.
+    pub storage: BTreeMap<B256, U256>,
+}
+
+/// Builds state and storage tries in memory, for fixtures that need no node or RPC.
+#[derive(Debug, Clone, Default)]
+pub struct StateBuilder {
+    accounts: BTreeMap<Address, FixtureAccount>,
+}
+
+impl StateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn account(mut self, address: Address, account: FixtureAccount) -> Self {
+        self.accounts.insert(address, account);
+        self
+    }
+
+    /// Builds the tries and a header at `block_number` committing to the state root.
+    pub fn build(&self, block_number: u64) -> StateFixture {
+        let storages: BTreeMap<Address, BTreeMap<B256, Vec<u8>>> = self
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                let leaves = account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| (keccak256(slot), alloy_rlp::encode(value)))
+                    .collect();
+                (*address, leaves)
+            })
+            .collect();
+
+        let accounts: BTreeMap<Address, Account> = self
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                let code_hash = if account.code.is_empty() {
+                    KECCAK_EMPTY
+                } else {
+                    keccak256(&account.code)
+                };
+                let account = Account {
+                    nonce: account.nonce,
+                    balance: account.balance,
+                    storage_root: trie(&storages[address], None).0,
+                    code_hash,
+                };
+                (*address, account)
+            })
+            .collect();
+
+        let state = accounts
+            .iter()
+            .map(|(address, account)| (keccak256(address), alloy_rlp::encode(account)))
+            .collect();
+        let state_root = trie(&state, None).0;
+
+        StateFixture {
+            header: Header {
+                number: block_number,
+                state_root,
+                ..Default::default()
+            },
+            accounts,
+            state,
+            storages,
+        }
+    }
+}
+
+/// Tries built by [`StateBuilder`], able to prove any account or slot, present or not.
+#[derive(Debug, Clone)]
+pub struct StateFixture {
+    pub header: Header,
+    accounts: BTreeMap<Address, Account>,
+    state: BTreeMap<B256, Vec<u8>>,
+    storages: BTreeMap<Address, BTreeMap<B256, Vec<u8>>>,
+}
+
+impl StateFixture {
+    pub fn state_root(&self) -> B256 {
+        self.header.state_root
+    }
+
+    pub fn account(&self, address: Address) -> Option<&Account> {
+        self.accounts.get(&address)
+    }
+
+    /// Account request with the proof and the claimed fields of `address`; an exclusion
+    /// proof with zero claims if the account does not exist.
+    pub fn account_proof(&self, address: Address) -> HdpAccount {
+        let key = keccak256(address);
+        let (_, proof) = trie(&self.state, Some(key));
+        let block_number = self.header.number;
+        let claim = match self.accounts.get(&address) {
+            Some(account) => AccountClaim {
+                block_number,
+                nonce: account.nonce,
+                balance: account.balance,
+                storage_root: account.storage_root,
+                code_hash: account.code_hash,
+            },
+            None => AccountClaim {
+                block_number,
+                nonce: 0,
+                balance: U256::ZERO,
+                storage_root: B256::ZERO,
+                code_hash: B256::ZERO,
+            },
+        };
+
+        HdpAccount {
+            address,
+            account_key: hex::encode(key),
+            proofs: vec![ProcessedMPTProof {
+                block_number,
+                proof,
+            }],
+            claims: vec![claim],
+        }
+    }
+
+    /// Storage request with the proof and the claimed value of `slot` of `address`.
+    pub fn storage_proof(&self, address: Address, slot: B256) -> HdpStorage {
+        let key = keccak256(slot);
+        let empty = BTreeMap::new();
+        let leaves = self.storages.get(&address).unwrap_or(&empty);
+        let (_, proof) = trie(leaves, Some(key));
+        let value = self.slot_value(address, slot).unwrap_or_default();
+
+        HdpStorage {
+            address,
+            slot,
+            storage_key: key,
+            proofs: vec![ProcessedMPTProof {
+                block_number: self.header.number,
+                proof,
+            }],
+            claims: vec![StorageClaim {
+                block_number: self.header.number,
+                value,
+            }],
+        }
+    }
+
+    fn slot_value(&self, address: Address, slot: B256) -> Option<U256> {
+        let encoded = self.storages.get(&address)?.get(&keccak256(slot))?;
+        alloy_rlp::Decodable::decode(&mut encoded.as_slice()).ok()
+    }
+}
+
+/// Root of the trie holding `leaves`, keyed by already hashed keys, and the proof of
+/// `target` if one is given.
+fn trie(leaves: &BTreeMap<B256, Vec<u8>>, target: Option<B256>) -> (B256, Vec<Bytes>) {
+    if leaves.is_empty() {
+        return (EMPTY_ROOT_HASH, vec![]);
+    }
+
+    let targets: Vec<Nibbles> = target.iter().map(Nibbles::unpack).collect();
+    let mut builder = HashBuilder::default().with_proof_retainer(ProofRetainer::new(targets));
+    for (key, value) in leaves {
+        builder.add_leaf(Nibbles::unpack(key), value);
+    }
+    let root = builder.root();
+
+    let proof = match target {
+        Some(target) => builder
+            .take_proof_nodes()
+            .matching_nodes_sorted(&Nibbles::unpack(target))
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect(),
+        None => vec![],
+    };
+    (root, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, b256};
+
+    use super::*;
+    use crate::{
+        eip1186::verify_claims,
+        mpt::{verify_account_proof, verify_storage},
+    };
+
+    const TOKEN: Address = address!("dac17f958d2ee523a2206206994597c13d831ec7");
+    const HOLDER: Address = address!("1a642f0e3c3af545e7acbd38b07251b3990914f1");
+    const SLOT_0: B256 = b256!("0000000000000000000000000000000000000000000000000000000000000000");
+    const SLOT_1: B256 = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+
+    fn fixture() -> StateFixture {
+        let mut builder = StateBuilder::new().account(
+            TOKEN,
+            FixtureAccount {
+                nonce: 1,
+                code: Bytes::from_static(&[0x60, 0x80]),
+                storage: BTreeMap::from([
+                    (SLOT_0, U256::from(42)),
+                    (SLOT_1, U256::ZERO),
+                    (B256::with_last_byte(7), U256::MAX),
+                ]),
+                ..Default::default()
+            },
+        );
+        for i in 1..=16u8 {
+            builder = builder.account(
+                Address::with_last_byte(i),
+                FixtureAccount {
+                    balance: U256::from(i),
+                    ..Default::default()
+                },
+            );
+        }
+        builder
+            .account(HOLDER, FixtureAccount::default())
+            .build(20000000)
+    }
+
+    #[test]
+    fn test_account_proofs() {
+        let fixture = fixture();
+        assert_eq!(fixture.header.number, 20000000);
+
+        for address in [TOKEN, HOLDER, Address::with_last_byte(9)] {
+            let account = fixture.account_proof(address);
+            let proof = account.proof_at(20000000).unwrap();
+            let proven = verify_account_proof(address, fixture.state_root(), proof).unwrap();
+            assert_eq!(proven.as_ref(), fixture.account(address));
+            verify_claims(fixture.state_root(), 20000000, &account, &[]).unwrap();
+        }
+
+        let token = fixture.account(TOKEN).unwrap();
+        assert_eq!(token.code_hash, keccak256([0x60, 0x80]));
+        assert_ne!(token.storage_root, EMPTY_ROOT_HASH);
+        assert_eq!(fixture.account(HOLDER).unwrap().code_hash, KECCAK_EMPTY);
+        assert_eq!(
+            fixture.account(HOLDER).unwrap().storage_root,
+            EMPTY_ROOT_HASH
+        );
+
+        let absent = address!("00000000000000000000000000000000deadbeef");
+        let account = fixture.account_proof(absent);
+        let proof = account.proof_at(20000000).unwrap();
+        assert_eq!(
+            verify_account_proof(absent, fixture.state_root(), proof).unwrap(),
+            None
+        );
+        verify_claims(fixture.state_root(), 20000000, &account, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_storage_proofs() {
+        let fixture = fixture();
+        let account = fixture.account_proof(TOKEN);
+        let storages: Vec<_> = [
+            SLOT_0,
+            SLOT_1,
+            B256::with_last_byte(7),
+            B256::with_last_byte(8),
+        ]
+        .into_iter()
+        .map(|slot| fixture.storage_proof(TOKEN, slot))
+        .collect();
+
+        let slots = verify_storage(fixture.state_root(), 20000000, &account, &storages).unwrap();
+        assert_eq!(slots[&SLOT_0], U256::from(42));
+        assert_eq!(slots[&SLOT_1], U256::ZERO);
+        assert_eq!(slots[&B256::with_last_byte(7)], U256::MAX);
+        assert_eq!(slots[&B256::with_last_byte(8)], U256::ZERO);
+        verify_claims(fixture.state_root(), 20000000, &account, &storages).unwrap();
+
+        // accounts without storage prove every slot empty
+        let account = fixture.account_proof(HOLDER);
+        let storage = fixture.storage_proof(HOLDER, SLOT_0);
+        assert!(storage.proofs[0].proof.is_empty());
+        let slots = verify_storage(fixture.state_root(), 20000000, &account, &[storage]).unwrap();
+        assert_eq!(slots[&SLOT_0], U256::ZERO);
+    }
+
+    #[test]
+    fn test_empty_state() {
+        let fixture = StateBuilder::new().build(1);
+        assert_eq!(fixture.state_root(), EMPTY_ROOT_HASH);
+
+        let account = fixture.account_proof(HOLDER);
+        assert!(account.proofs[0].proof.is_empty());
+        verify_claims(fixture.state_root(), 1, &account, &[]).unwrap();
+    }
+}
@@ -1,7 +1,44 @@
 use alloy_primitives::Keccak256 as AlloyKeccak256;
 use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
 use starknet_types_core::{felt::Felt, hash::StarkHash};
 
+/// Hash function an MMR was built with, carried in the input so the matching guest ELF
+/// is picked. Inputs without one are Keccak.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum HashId {
+    #[default]
+    Keccak,
+    Poseidon,
+}
+
+impl TryFrom<u8> for HashId {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Keccak),
+            1 => Ok(Self::Poseidon),
+            _ => Err(value),
+        }
+    }
+}
+
+impl std::str::FromStr for HashId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keccak" => Ok(Self::Keccak),
+            "poseidon" => Ok(Self::Poseidon),
+            _ => Err(format!(
+                "unknown hash `{s}`, expected `keccak` or `poseidon`"
+            )),
+        }
+    }
+}
+
 pub trait Hash {
     type HeaderType;
     type HashOutput;
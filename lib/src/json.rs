@@ -1,15 +1,20 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    account::HdpAccount,
-    mmr_keccak::{Header, MmrMeta},
-    storage::HdpStorage,
+    account::HdpAccount, diff::HdpDiff, hash::HashId, mmr, mmr_keccak, storage::HdpStorage,
 };
 
+/// Guest input. `meta` and `headers` are the types of the MMR named by `hash_id`.
 #[derive(Serialize, Deserialize)]
-pub struct MmrJson {
-    pub meta: MmrMeta,
-    pub headers: Vec<Header>,
+pub struct MmrJson<M = mmr_keccak::MmrMeta, H = mmr_keccak::Header> {
+    #[serde(default)]
+    pub hash_id: HashId,
+    pub meta: M,
+    pub headers: Vec<H>,
     pub accounts: Vec<HdpAccount>,
     pub storages: Vec<HdpStorage>,
+    #[serde(default)]
+    pub diffs: Vec<HdpDiff>,
 }
+
+pub type PoseidonMmrJson = MmrJson<mmr::MmrMeta, mmr::Header>;
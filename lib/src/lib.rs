@@ -1,8 +1,33 @@
 pub mod account;
+pub mod bytecode;
+pub mod chunks;
+pub mod diff;
+pub mod eip1186;
+pub mod fixture;
 pub mod hash;
 pub mod json;
+pub mod logs;
 pub mod mmr;
 pub mod mmr_keccak;
 pub mod mpt;
+pub mod mpt_cached;
+pub mod multiproof;
+pub mod outcome;
+pub mod proxy;
+pub mod receipt;
 pub mod rlp;
 pub mod storage;
+pub mod storage_layout;
+pub mod storage_value;
+pub mod token;
+pub mod transaction;
+pub mod verify;
+pub mod withdrawal;
+
+/// MMR the guest verifies headers against: `mmr_keccak` unless built with `poseidon-mmr`,
+/// which wins over the default `keccak-mmr` so `--all-features` builds. Both modules are
+/// always available to the host.
+#[cfg(feature = "poseidon-mmr")]
+pub use mmr as guest_mmr;
+#[cfg(not(feature = "poseidon-mmr"))]
+pub use mmr_keccak as guest_mmr;
@@ -0,0 +1,206 @@
+use alloy_primitives::{Address, Bloom, BloomInput, Log, B256};
+use reth_primitives::Header;
+use serde::{Deserialize, Serialize};
+
+use crate::receipt::ProvenReceipt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogsError {
+    #[error("logs bloom of block {0} may contain a matching log")]
+    BloomHit(u64),
+}
+
+/// Matches logs by emitting address and topic0..3, with `eth_getLogs` semantics:
+/// an empty `addresses` or topic position matches anything, otherwise the log's
+/// value must be one of the listed ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFilter {
+    pub addresses: Vec<Address>,
+    pub topics: [Vec<B256>; 4],
+}
+
+impl LogFilter {
+    pub fn new(addresses: Vec<Address>, topics: [Vec<B256>; 4]) -> Self {
+        Self { addresses, topics }
+    }
+
+    pub fn matches(&self, log: &Log) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+
+        let log_topics = log.topics();
+        self.topics.iter().enumerate().all(|(i, accepted)| {
+            accepted.is_empty()
+                || log_topics
+                    .get(i)
+                    .is_some_and(|topic| accepted.contains(topic))
+        })
+    }
+
+    /// Returns `false` only if no log in a block with this bloom can match the filter,
+    /// i.e. none of the accepted values of some non-empty position is set in the bloom.
+    pub fn may_match_bloom(&self, bloom: &Bloom) -> bool {
+        // an empty bloom means the block emitted no logs at all
+        if *bloom == Bloom::ZERO {
+            return false;
+        }
+
+        let addresses_hit = self.addresses.is_empty()
+            || self
+                .addresses
+                .iter()
+                .any(|address| bloom_contains_address(bloom, *address));
+
+        addresses_hit
+            && self.topics.iter().all(|accepted| {
+                accepted.is_empty()
+                    || accepted
+                        .iter()
+                        .any(|topic| bloom_contains_topic(bloom, *topic))
+            })
+    }
+}
+
+pub fn bloom_contains_address(bloom: &Bloom, address: Address) -> bool {
+    bloom.contains_input(BloomInput::Raw(address.as_slice()))
+}
+
+pub fn bloom_contains_topic(bloom: &Bloom, topic: B256) -> bool {
+    bloom.contains_input(BloomInput::Raw(topic.as_slice()))
+}
+
+/// Proves that the block of `header` emitted no log matching `filter`, using its
+/// `logs_bloom` only. The header must already be verified against the MMR.
+/// Fails with [`LogsError::BloomHit`] when the bloom is inconclusive, in which case the
+/// block's receipts have to be proven instead.
+pub fn prove_no_matching_logs(header: &Header, filter: &LogFilter) -> Result<(), LogsError> {
+    if filter.may_match_bloom(&header.logs_bloom) {
+        return Err(LogsError::BloomHit(header.number));
+    }
+    Ok(())
+}
+
+/// A log matched by a [`LogFilter`]. `log_index` is the position of the log within its
+/// transaction's receipt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedLog {
+    pub block_number: u64,
+    pub tx_index: u64,
+    pub log_index: u64,
+    pub log: Log,
+}
+
+/// Returns every log of the given proven receipts matching `filter`, in receipt order.
+pub fn filter_logs(receipts: &[ProvenReceipt], filter: &LogFilter) -> Vec<MatchedLog> {
+    let mut matched = vec![];
+    for receipt in receipts {
+        for (log_index, log) in receipt.receipt.logs().iter().enumerate() {
+            if filter.matches(log) {
+                matched.push(MatchedLog {
+                    block_number: receipt.block_number,
+                    tx_index: receipt.tx_index,
+                    log_index: log_index as u64,
+                    log: log.clone(),
+                });
+            }
+        }
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use reth_primitives::{address, b256, hex};
+
+    use super::*;
+    use crate::receipt::decode_receipt;
+
+    const TOKEN: Address = address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4");
+    const TRANSFER: B256 =
+        b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+    fn receipts() -> Vec<ProvenReceipt> {
+        let encoded = [
+            hex!("f902440182c738b9010000000000000000000000000000000002000000000000000000000000000000000000202000000000000000000000000000080000000004000000800020200000000000000000000000000008000400000000000000000000000000000000000000000000080000000000000000000000000000008000000000000010000000000000000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000080000002000000000000000000000000000000000000000000000000000000000010000000000000000000000000000008000800100000800000000000000000f9013af89b947f2c6f930306d3aa736b3a6c6a98f512f74036d4f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000001a642f0e3c3af545e7acbd38b07251b3990914f1a00000000000000000000000002222222222222222222222222222222222222222a000000000000000000000000000000000000000000000000000000000000003e8f89b9475cec1db9dceb703200eaa6595f66885c962b920f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000001a642f0e3c3af545e7acbd38b07251b3990914f1a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000005").to_vec(),
+            hex!("02f901a70183018e70b9010000000000000000000000000000000002000000000000000000000000000000000000202000000000000000000000000000080000000004000000000020000000000000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080000002000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000800100000000000000000000000f89df89b947f2c6f930306d3aa736b3a6c6a98f512f74036d4f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000002222222222222222222222222222222222222222a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000190").to_vec(),
+            hex!("02f90109808301e078b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c0").to_vec(),
+        ];
+
+        encoded
+            .iter()
+            .enumerate()
+            .map(|(i, receipt)| ProvenReceipt {
+                block_number: 5641516,
+                tx_index: i as u64,
+                receipt: decode_receipt(receipt).unwrap(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_erc20_transfers() {
+        let filter = LogFilter::new(vec![TOKEN], [vec![TRANSFER], vec![], vec![], vec![]]);
+        let matched = filter_logs(&receipts(), &filter);
+
+        assert_eq!(matched.len(), 2);
+        assert_eq!((matched[0].tx_index, matched[0].log_index), (0, 0));
+        assert_eq!((matched[1].tx_index, matched[1].log_index), (1, 0));
+        assert_eq!(
+            U256::from_be_slice(&matched[1].log.data.data),
+            U256::from(400)
+        );
+    }
+
+    #[test]
+    fn test_bloom_absence() {
+        // logs bloom of the first receipt: a Transfer on TOKEN and an Approval on another contract
+        let header = Header {
+            number: 5641516,
+            logs_bloom: Bloom::new(hex!("00000000000000000000000000000002000000000000000000000000000000000000202000000000000000000000000000080000000004000000800020200000000000000000000000000008000400000000000000000000000000000000000000000000080000000000000000000000000000008000000000000010000000000000000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000080000002000000000000000000000000000000000000000000000000000000000010000000000000000000000000000008000800100000800000000000000000")),
+            ..Default::default()
+        };
+        let approval = b256!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+
+        assert!(bloom_contains_address(&header.logs_bloom, TOKEN));
+        assert!(bloom_contains_topic(&header.logs_bloom, TRANSFER));
+
+        let transfers = LogFilter::new(vec![TOKEN], [vec![TRANSFER], vec![], vec![], vec![]]);
+        assert!(matches!(
+            prove_no_matching_logs(&header, &transfers),
+            Err(LogsError::BloomHit(5641516))
+        ));
+
+        // a bloom hit on each input separately is still inconclusive
+        let approvals = LogFilter::new(vec![TOKEN], [vec![approval], vec![], vec![], vec![]]);
+        assert!(prove_no_matching_logs(&header, &approvals).is_err());
+
+        let unknown_sender = address!("4444444444444444444444444444444444444444").into_word();
+        let from_unknown = LogFilter::new(
+            vec![TOKEN],
+            [vec![TRANSFER], vec![unknown_sender], vec![], vec![]],
+        );
+        assert!(prove_no_matching_logs(&header, &from_unknown).is_ok());
+
+        let empty_block = Header::default();
+        assert!(prove_no_matching_logs(&empty_block, &transfers).is_ok());
+        assert!(prove_no_matching_logs(&empty_block, &LogFilter::default()).is_ok());
+    }
+
+    #[test]
+    fn test_filter_by_indexed_topic() {
+        let recipient = address!("3333333333333333333333333333333333333333").into_word();
+
+        // any event with `recipient` as its second indexed argument
+        let filter = LogFilter::new(vec![], [vec![], vec![], vec![recipient], vec![]]);
+        let matched = filter_logs(&receipts(), &filter);
+        assert_eq!(matched.len(), 2);
+        assert_eq!((matched[0].tx_index, matched[0].log_index), (0, 1));
+        assert_eq!((matched[1].tx_index, matched[1].log_index), (1, 0));
+
+        // Transfer events only have three topics
+        let filter = LogFilter::new(vec![], [vec![TRANSFER], vec![], vec![], vec![B256::ZERO]]);
+        assert!(filter_logs(&receipts(), &filter).is_empty());
+    }
+}
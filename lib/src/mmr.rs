@@ -7,14 +7,28 @@ pub enum MmrError {
 
     #[error("InvalidElementIndex")]
     InvalidElementIndex,
+
+    #[error("RootMismatch")]
+    RootMismatch,
+
+    #[error("PeaksCountMismatch")]
+    PeaksCountMismatch,
 }
 
+use alloy_primitives::B256;
 use serde::{Deserialize, Serialize};
 use starknet_types_core::{
     felt::Felt,
     hash::{Poseidon, StarkHash},
 };
 
+use crate::{
+    chunks::{bytes_to_felts, felts_to_rlp, ChunkError},
+    hash::HashId,
+};
+
+pub const HASH_ID: HashId = HashId::Poseidon;
+
 #[derive(Serialize, Deserialize)]
 pub struct MmrMeta {
     pub root: Felt,
@@ -27,6 +41,11 @@ impl MmrMeta {
         Self { root, size, peaks }
     }
 
+    /// The root as a big-endian word, as committed in the public values.
+    pub fn root_word(&self) -> B256 {
+        B256::from(self.root.to_bytes_be())
+    }
+
     pub fn verify_proof(
         &self,
         element_index: u128,
@@ -35,13 +54,20 @@ impl MmrMeta {
     ) -> Result<bool, Box<dyn Error>> {
         // Ensure the root hash matches the calculated root from the peaks
         let root = self.bag_peaks()?;
-        assert_eq!(root, self.root);
+        if root != self.root {
+            return Err(Box::new(MmrError::RootMismatch));
+        }
 
         let leaf_count = mmr_size_to_leaf_count(self.size as usize);
         let peaks_count = leaf_count_to_peaks_count(leaf_count);
 
-        assert_eq!(peaks_count, self.peaks.len() as u32);
+        if peaks_count != self.peaks.len() as u32 {
+            return Err(Box::new(MmrError::PeaksCountMismatch));
+        }
 
+        if element_index > self.size {
+            return Err(Box::new(MmrError::InvalidElementIndex));
+        }
         let mut hash = element_value;
         let mut leaf_index = element_index_to_leaf_index(element_index as usize)?;
 
@@ -62,14 +88,14 @@ impl MmrMeta {
 
         // Get the peak information
         let (peak_index, _) = get_peak_info(self.size as usize, element_index as usize);
-        let peak_hashes = self.peaks.clone();
+        let peak_hash = self.peaks.get(peak_index).ok_or(MmrError::PeaksError)?;
 
         // Verify the final hash matches the peak hash
-        Ok(peak_hashes[peak_index] == hash)
+        Ok(*peak_hash == hash)
     }
 
     /// P = Poseidon(N | Poseidon(N | Node(p1) | Node(p2) | Node(p3))), N = size, p = peaks
-    fn bag_peaks(&self) -> Result<Felt, Box<dyn Error>> {
+    pub fn bag_peaks(&self) -> Result<Felt, Box<dyn Error>> {
         let final_top_peak = self.final_top_peak()?;
         let size = Felt::from(self.size);
 
@@ -151,7 +177,9 @@ pub fn mmr_size_to_leaf_count(mmr_size: usize) -> usize {
 }
 
 pub fn element_index_to_leaf_index(element_index: usize) -> Result<usize, Box<dyn Error>> {
-    assert!(element_index > 0);
+    if element_index == 0 {
+        return Err(Box::new(MmrError::InvalidElementIndex));
+    }
     elements_count_to_leaf_count(element_index - 1)
 }
 
@@ -182,15 +210,46 @@ pub struct Header {
     pub proof: HeaderInclusionProof,
 }
 
+impl Header {
+    pub fn from_rlp_bytes(rlp: &[u8], proof: HeaderInclusionProof) -> Self {
+        Self {
+            rlp: bytes_to_felts(rlp),
+            proof,
+        }
+    }
+
+    /// Raw RLP bytes of the header, recovered from the u64 felt words.
+    pub fn rlp_bytes(&self) -> Result<Vec<u8>, ChunkError> {
+        felts_to_rlp(&self.rlp)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HeaderInclusionProof {
     pub leaf_idx: u128,
     pub mmr_path: Vec<Felt>,
 }
 
-pub fn verify_headers_with_mmr_peaks(mmr: MmrMeta, header: Header) -> Result<bool, Box<dyn Error>> {
+/// Whether `header` is the leaf its inclusion proof points at.
+pub fn verify_header(mmr: &MmrMeta, header: &Header) -> Result<bool, Box<dyn Error>> {
     let element_value = Poseidon::hash_array(&header.rlp);
-    mmr.verify_proof(header.proof.leaf_idx, element_value, header.proof.mmr_path)
+    mmr.verify_proof(
+        header.proof.leaf_idx,
+        element_value,
+        header.proof.mmr_path.clone(),
+    )
+}
+
+pub fn verify_headers_with_mmr_peaks(
+    mmr: MmrMeta,
+    headers: &[Header],
+) -> Result<bool, Box<dyn Error>> {
+    for header in headers {
+        if !verify_header(&mmr, header)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }
 
 pub fn validate_mmr(mmr: MmrMeta) {
@@ -225,6 +284,8 @@ fn validate_mmr_size(size: u128) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use alloy_rlp::Decodable;
+
     use super::*;
 
     #[test]
@@ -484,6 +545,21 @@ mod tests {
             },
         };
 
-        assert!(verify_headers_with_mmr_peaks(test_mmr_meta, test_header).unwrap());
+        let rlp = test_header.rlp_bytes().unwrap();
+        let decoded = reth_primitives::Header::decode(&mut rlp.as_slice()).unwrap();
+        assert_eq!(decoded.number, 5858987);
+        assert_eq!(
+            Header::from_rlp_bytes(
+                &rlp,
+                HeaderInclusionProof {
+                    leaf_idx: 0,
+                    mmr_path: vec![]
+                }
+            )
+            .rlp,
+            test_header.rlp
+        );
+
+        assert!(verify_headers_with_mmr_peaks(test_mmr_meta, &[test_header]).unwrap());
     }
 }
@@ -7,6 +7,12 @@ pub enum MmrError {
 
     #[error("InvalidElementIndex")]
     InvalidElementIndex,
+
+    #[error("RootMismatch")]
+    RootMismatch,
+
+    #[error("PeaksCountMismatch")]
+    PeaksCountMismatch,
 }
 
 use alloy_primitives::hex;
@@ -14,7 +20,9 @@ use alloy_primitives::{B256, U256};
 use reth_primitives::keccak256;
 use serde::{Deserialize, Serialize};
 
-use crate::hash::{Hash, Keccak256};
+use crate::hash::{Hash, HashId, Keccak256};
+
+pub const HASH_ID: HashId = HashId::Keccak;
 
 #[derive(Serialize, Deserialize)]
 pub struct MmrMeta {
@@ -28,6 +36,11 @@ impl MmrMeta {
         Self { root, size, peaks }
     }
 
+    /// The root, as committed in the public values.
+    pub fn root_word(&self) -> B256 {
+        self.root
+    }
+
     pub fn verify_proof(
         &self,
         element_index: u128,
@@ -36,13 +49,20 @@ impl MmrMeta {
     ) -> Result<bool, Box<dyn Error>> {
         // Ensure the root hash matches the calculated root from the peaks
         let root = self.bag_peaks()?;
-        assert_eq!(root, self.root);
+        if root != self.root {
+            return Err(Box::new(MmrError::RootMismatch));
+        }
 
         let leaf_count = mmr_size_to_leaf_count(self.size as usize);
         let peaks_count = leaf_count_to_peaks_count(leaf_count);
 
-        assert_eq!(peaks_count, self.peaks.len() as u32);
+        if peaks_count != self.peaks.len() as u32 {
+            return Err(Box::new(MmrError::PeaksCountMismatch));
+        }
 
+        if element_index > self.size {
+            return Err(Box::new(MmrError::InvalidElementIndex));
+        }
         let mut hash = element_value;
         let mut leaf_index = element_index_to_leaf_index(element_index as usize)?;
 
@@ -63,13 +83,13 @@ impl MmrMeta {
 
         // Get the peak information
         let (peak_index, _) = get_peak_info(self.size as usize, element_index as usize);
-        let peak_hashes = self.peaks.clone();
+        let peak_hash = self.peaks.get(peak_index).ok_or(MmrError::PeaksError)?;
         // Verify the final hash matches the peak hash
-        Ok(peak_hashes[peak_index] == hash)
+        Ok(*peak_hash == hash)
     }
 
     /// P = Poseidon(N | Poseidon(N | Node(p1) | Node(p2) | Node(p3))), N = size, p = peaks
-    fn bag_peaks(&self) -> Result<B256, Box<dyn Error>> {
+    pub fn bag_peaks(&self) -> Result<B256, Box<dyn Error>> {
         let final_top_peak = self.final_top_peak()?;
         let size: B256 = U256::from(self.size).into();
         Ok(Keccak256::hash(size, final_top_peak))
@@ -150,7 +170,9 @@ pub fn mmr_size_to_leaf_count(mmr_size: usize) -> usize {
 }
 
 pub fn element_index_to_leaf_index(element_index: usize) -> Result<usize, Box<dyn Error>> {
-    assert!(element_index > 0);
+    if element_index == 0 {
+        return Err(Box::new(MmrError::InvalidElementIndex));
+    }
     elements_count_to_leaf_count(element_index - 1)
 }
 
@@ -181,27 +203,45 @@ pub struct Header {
     pub proof: HeaderInclusionProof,
 }
 
+impl Header {
+    pub fn from_rlp_bytes(rlp: &[u8], proof: HeaderInclusionProof) -> Self {
+        Self {
+            rlp: hex::encode(rlp),
+            proof,
+        }
+    }
+
+    pub fn rlp_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        hex::decode(&self.rlp)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HeaderInclusionProof {
     pub leaf_idx: u128,
     pub mmr_path: Vec<B256>,
 }
 
+/// Whether `header` is the leaf its inclusion proof points at.
+pub fn verify_header(mmr: &MmrMeta, header: &Header) -> Result<bool, Box<dyn Error>> {
+    let element_value = keccak256(header.rlp_bytes()?);
+    mmr.verify_proof(
+        header.proof.leaf_idx,
+        element_value,
+        header.proof.mmr_path.clone(),
+    )
+}
+
 pub fn verify_headers_with_mmr_peaks(
     mmr: MmrMeta,
     headers: &[Header],
 ) -> Result<bool, Box<dyn Error>> {
-    let mut is_verified = true;
     for header in headers {
-        let rlp_bytes = hex::decode(&header.rlp).unwrap();
-        let element_value = keccak256(rlp_bytes);
-        is_verified = mmr.verify_proof(
-            header.proof.leaf_idx,
-            element_value,
-            header.proof.mmr_path.clone(),
-        )?;
+        if !verify_header(&mmr, header)? {
+            return Ok(false);
+        }
     }
-    Ok(is_verified)
+    Ok(true)
 }
 
 pub fn validate_mmr(mmr: MmrMeta) {
@@ -295,6 +335,38 @@ mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn verify_proof_errors() {
+        let mut test_mmr_meta: MmrMeta = MmrMeta {
+            root: B256::from_hex(
+                "0xa7122a01868e54648facd92a3a821fae03301a71d1bd02fabe4e82bffcbd0aeb",
+            )
+            .unwrap(),
+            size: 11,
+            peaks: vec![
+                B256::from_hex(
+                    "0xbf874bd367f32d74d7d084a8eb85ce99d6f2622fbc0d1f83dcd0c4404f8e0cea",
+                )
+                .unwrap(),
+                B256::from_hex(
+                    "0x04cde762ef08b6b6c5ded8e8c4c0b3f4e5c9ad7342c88fcc93681b4588b73f05",
+                )
+                .unwrap(),
+                B256::from(U256::from(3)),
+            ],
+        };
+        let error = |meta: &MmrMeta, index| {
+            let err = meta.verify_proof(index, B256::ZERO, vec![]).unwrap_err();
+            format!("{:?}", err.downcast_ref::<MmrError>().unwrap())
+        };
+
+        assert_eq!(error(&test_mmr_meta, 0), "InvalidElementIndex");
+        assert_eq!(error(&test_mmr_meta, 12), "InvalidElementIndex");
+
+        test_mmr_meta.root = B256::ZERO;
+        assert_eq!(error(&test_mmr_meta, 8), "RootMismatch");
+    }
+
     #[test]
     fn test_verify_headers_with_mmr_peaks() {
         let test_mmr_meta: MmrMeta = MmrMeta {
@@ -1,9 +1,130 @@
-use alloy_primitives::B256;
-use alloy_trie::proof::verify_proof;
-use reth_primitives::{hex, Bytes};
+use std::collections::HashMap;
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_rlp::Decodable;
+use alloy_trie::{
+    nodes::TrieNode,
+    proof::{verify_proof, ProofVerificationError},
+};
+use reth_primitives::{hex, Bytes, EMPTY_ROOT_HASH};
 use reth_trie::{Nibbles, StorageProof};
 
-use crate::{account::HdpAccount, storage::HdpStorage};
+use crate::{account::HdpAccount, rlp::Account, storage::HdpStorage};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MptError {
+    #[error("EmptyProof")]
+    EmptyProof,
+
+    #[error("MissingLeaf")]
+    MissingLeaf,
+
+    #[error("invalid trie node: {0}")]
+    Rlp(#[from] alloy_rlp::Error),
+
+    #[error("proof verification failed: {0}")]
+    Verification(#[from] ProofVerificationError),
+
+    #[error("InvalidNode")]
+    InvalidNode,
+
+    #[error("node does not hash to {0}")]
+    HashMismatch(B256),
+
+    #[error("proof ends before node {0}")]
+    MissingNode(B256),
+
+    #[error("no proof for block {0}")]
+    MissingBlockProof(u64),
+
+    #[error("storage proof is for {storage}, account proof is for {account}")]
+    AddressMismatch { account: Address, storage: Address },
+}
+
+/// Verifies an inclusion proof for an unhashed `key` and returns the value held by its leaf.
+/// Used for the transaction, receipt and withdrawal tries, which are keyed by `rlp(index)`.
+pub fn verify_inclusion(root: B256, key: &[u8], proof: &[Bytes]) -> Result<Vec<u8>, MptError> {
+    let last = proof.last().ok_or(MptError::EmptyProof)?;
+    let value = match TrieNode::decode(&mut last.as_ref())? {
+        TrieNode::Leaf(leaf) => leaf.value,
+        _ => return Err(MptError::MissingLeaf),
+    };
+
+    verify_proof(root, Nibbles::unpack(key), Some(value.clone()), proof)?;
+    Ok(value)
+}
+
+/// Verifies either an inclusion or an exclusion proof for `key`, returning the leaf value
+/// if the key is in the trie and `None` if the proof shows it is absent.
+pub fn verify_value(root: B256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>, MptError> {
+    match verify_inclusion(root, key, proof) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => {
+            verify_proof(root, Nibbles::unpack(key), None, proof)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Verifies the account proof of `address` against `state_root` and decodes the account.
+/// Returns `None` for an account that does not exist in the state.
+pub fn verify_account_proof(
+    address: Address,
+    state_root: B256,
+    proof: &[Bytes],
+) -> Result<Option<Account>, MptError> {
+    verify_value(state_root, keccak256(address).as_slice(), proof)?
+        .map(|value| Account::decode(&mut value.as_slice()))
+        .transpose()
+        .map_err(MptError::from)
+}
+
+/// Verifies the storage proof of `slot` against an account's `storage_root`.
+/// A slot that is not in the trie holds zero.
+pub fn verify_storage_proof(
+    storage_root: B256,
+    slot: B256,
+    proof: &[Bytes],
+) -> Result<U256, MptError> {
+    verify_value(storage_root, keccak256(slot).as_slice(), proof)?
+        .map(|value| U256::decode(&mut value.as_slice()))
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(MptError::from)
+}
+
+/// Verifies the account proof and the storage proofs of one contract at `block_number`,
+/// returning the proven value of every requested slot. A missing account has empty storage.
+pub fn verify_storage(
+    state_root: B256,
+    block_number: u64,
+    account: &HdpAccount,
+    storages: &[HdpStorage],
+) -> Result<HashMap<B256, U256>, MptError> {
+    let account_proof = account
+        .proof_at(block_number)
+        .ok_or(MptError::MissingBlockProof(block_number))?;
+    let storage_root = verify_account_proof(account.address, state_root, account_proof)?
+        .map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+
+    let mut slots = HashMap::with_capacity(storages.len());
+    for storage in storages {
+        if storage.address != account.address {
+            return Err(MptError::AddressMismatch {
+                account: account.address,
+                storage: storage.address,
+            });
+        }
+        let proof = storage
+            .proof_at(block_number)
+            .ok_or(MptError::MissingBlockProof(block_number))?;
+        slots.insert(
+            storage.slot,
+            verify_storage_proof(storage_root, storage.slot, proof)?,
+        );
+    }
+    Ok(slots)
+}
 
 pub fn verify_account(account: HdpAccount, state_root: B256) -> bool {
     for proof in account.proofs {
@@ -25,14 +146,9 @@ pub fn verify_account(account: HdpAccount, state_root: B256) -> bool {
     true
 }
 
-pub fn into_storage_proof(storage: Option<HdpStorage>) -> Vec<StorageProof> {
-    let mut vec_storage_proofs = vec![];
-    for proof in storage.clone().unwrap().proofs {
-        let mut storage_proof = StorageProof::new(storage.clone().unwrap().storage_key);
-        storage_proof.proof = proof.proof;
-        vec_storage_proofs.push(storage_proof);
-    }
-    vec_storage_proofs
+/// One reth `StorageProof` per block of `storage`; see `From<&HdpStorage>`.
+pub fn into_storage_proof(storage: &HdpStorage) -> Vec<StorageProof> {
+    storage.into()
 }
 
 #[cfg(test)]
@@ -43,10 +159,13 @@ mod tests {
     use reth_primitives::{address, b256, bytes, hex, Bytes, B256};
     use reth_trie::Nibbles;
 
+    use super::*;
+    use crate::eip1186::{into_account_proofs, verify_claims, BlockProofResponse, Eip1186Error};
+
     #[test]
     fn test_eip_1186_account_without_storage_proof() {
         // TEST CASE: account proof of ETHEREUM SEPOLIA 6127485
-        let _ = EIP1186AccountProofResponse {
+        let response = EIP1186AccountProofResponse {
             address: address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4"),
             balance: U256::from_str_radix("21422802379747620244", 10).unwrap(),
             code_hash: b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
@@ -65,11 +184,29 @@ mod tests {
             storage_proof: vec![],
         };
 
-        // let state_root =
-        //     B256::from_str("0x6f184a0cf582192768fc6c8c697da0e9eb85b623c0cfea2b26034e29cdc88628")
-        //         .unwrap();
-        // let account_proof = AccountProofWithBytecode::from_eip1186_proof(res);
-        // account_proof.verify(state_root).unwrap();
+        let state_root = b256!("6f184a0cf582192768fc6c8c697da0e9eb85b623c0cfea2b26034e29cdc88628");
+        let account = HdpAccount::from(BlockProofResponse {
+            block_number: 6127485,
+            response: response.clone(),
+        });
+        assert_eq!(
+            account.account_key,
+            hex::encode(keccak256(response.address))
+        );
+        verify_claims(state_root, 6127485, &account, &[]).unwrap();
+
+        let account_proof = &into_account_proofs(&account, &[])[0];
+        assert_eq!(account_proof.proof, response.account_proof);
+        assert_eq!(account_proof.storage_root, response.storage_hash);
+        assert_eq!(account_proof.info.unwrap().nonce, 119083);
+        assert_eq!(account_proof.info.unwrap().bytecode_hash, None);
+
+        let mut tampered = account;
+        tampered.claims[0].nonce += 1;
+        assert!(matches!(
+            verify_claims(state_root, 6127485, &tampered, &[]),
+            Err(Eip1186Error::AccountClaimMismatch(6127485))
+        ));
     }
 
     #[test]
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Header};
+use reth_primitives::EMPTY_ROOT_HASH;
+
+use crate::{mpt::MptError, rlp::Account};
+
+/// MPT proof verifier for the guest. Nodes are decoded in place from the proof bytes, the
+/// key is compared nibble by nibble without unpacking it, and every node hash checked
+/// against `root` is cached, so nodes shared by several proofs are hashed once.
+#[derive(Debug, Clone)]
+pub struct CachedVerifier<'a> {
+    root: B256,
+    verified: HashMap<B256, &'a [u8]>,
+}
+
+/// Reference from a parent node: a hash, or the encoding of a node under 32 bytes.
+enum NodeRef<'a> {
+    Hash(B256),
+    Inline(&'a [u8]),
+}
+
+impl<'a> CachedVerifier<'a> {
+    pub fn new(root: B256) -> Self {
+        Self {
+            root,
+            verified: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> B256 {
+        self.root
+    }
+
+    /// Number of distinct nodes hashed so far.
+    pub fn hashed_nodes(&self) -> usize {
+        self.verified.len()
+    }
+
+    /// Verifies an inclusion or exclusion proof of `key` and returns the leaf value, borrowed
+    /// from the proof, or `None` if the key is proven absent.
+    pub fn verify(&mut self, key: &[u8], proof: &'a [Bytes]) -> Result<Option<&'a [u8]>, MptError> {
+        if self.root == EMPTY_ROOT_HASH && proof.is_empty() {
+            return Ok(None);
+        }
+
+        let mut nodes = proof.iter();
+        let mut next = NodeRef::Hash(self.root);
+        let mut depth = 0;
+        loop {
+            let node = match next {
+                NodeRef::Hash(hash) => {
+                    let node = nodes.next().ok_or(MptError::MissingNode(hash))?;
+                    self.check(hash, node)?;
+                    node.as_ref()
+                }
+                NodeRef::Inline(node) => node,
+            };
+
+            let mut items: [&[u8]; 17] = [&[]; 17];
+            let mut count = 0;
+            for item in list_items(node)? {
+                *items.get_mut(count).ok_or(MptError::InvalidNode)? = item?;
+                count += 1;
+            }
+
+            match count {
+                // extension or leaf: hex-prefix encoded path, then child or value
+                2 => {
+                    let path = string_payload(items[0])?;
+                    let flag = *path.first().ok_or(MptError::InvalidNode)? >> 4;
+                    let skip = if flag & 1 != 0 { 1 } else { 2 };
+                    let len = path.len() * 2 - skip;
+                    if depth + len > key.len() * 2
+                        || (0..len).any(|i| nibble(path, skip + i) != nibble(key, depth + i))
+                    {
+                        return Ok(None);
+                    }
+                    depth += len;
+
+                    if flag & 2 != 0 {
+                        return (depth == key.len() * 2)
+                            .then(|| string_payload(items[1]))
+                            .transpose();
+                    }
+                    next = node_ref(items[1])?;
+                }
+                // branch: 16 children and a value slot, unused in state and storage tries
+                17 => {
+                    if depth == key.len() * 2 {
+                        return Err(MptError::InvalidNode);
+                    }
+                    let child = items[nibble(key, depth) as usize];
+                    if child == [alloy_rlp::EMPTY_STRING_CODE] {
+                        return Ok(None);
+                    }
+                    depth += 1;
+                    next = node_ref(child)?;
+                }
+                _ => return Err(MptError::InvalidNode),
+            }
+        }
+    }
+
+    /// Verifies the account proof of `address` and decodes the account.
+    pub fn account(
+        &mut self,
+        address: Address,
+        proof: &'a [Bytes],
+    ) -> Result<Option<Account>, MptError> {
+        self.verify(keccak256(address).as_slice(), proof)?
+            .map(|mut value| Account::decode(&mut value))
+            .transpose()
+            .map_err(MptError::from)
+    }
+
+    /// Verifies the storage proof of `slot`; the verifier's root is the account's storage root.
+    pub fn storage(&mut self, slot: B256, proof: &'a [Bytes]) -> Result<U256, MptError> {
+        self.verify(keccak256(slot).as_slice(), proof)?
+            .map(|mut value| U256::decode(&mut value))
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(MptError::from)
+    }
+
+    fn check(&mut self, hash: B256, node: &'a [u8]) -> Result<(), MptError> {
+        if self.verified.get(&hash) == Some(&node) {
+            return Ok(());
+        }
+        if keccak256(node) != hash {
+            return Err(MptError::HashMismatch(hash));
+        }
+        self.verified.insert(hash, node);
+        Ok(())
+    }
+}
+
+fn nibble(bytes: &[u8], index: usize) -> u8 {
+    let byte = bytes[index / 2];
+    if index % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+fn node_ref(item: &[u8]) -> Result<NodeRef<'_>, MptError> {
+    match item {
+        [0xa0, hash @ ..] if hash.len() == 32 => Ok(NodeRef::Hash(B256::from_slice(hash))),
+        [first, ..] if *first >= alloy_rlp::EMPTY_LIST_CODE => Ok(NodeRef::Inline(item)),
+        _ => Err(MptError::InvalidNode),
+    }
+}
+
+/// Payload of an RLP string item.
+fn string_payload(mut item: &[u8]) -> Result<&[u8], MptError> {
+    let header = Header::decode(&mut item)?;
+    if header.list {
+        return Err(MptError::InvalidNode);
+    }
+    item.get(..header.payload_length)
+        .ok_or(MptError::InvalidNode)
+}
+
+/// Full encodings of the items of an RLP list, which has to span `node` exactly. An item
+/// that does not decode or runs past the list is an error, not the end of the list.
+fn list_items(mut node: &[u8]) -> Result<impl Iterator<Item = Result<&[u8], MptError>>, MptError> {
+    let header = Header::decode(&mut node)?;
+    if !header.list || node.len() != header.payload_length {
+        return Err(MptError::InvalidNode);
+    }
+    let mut payload = node;
+    Ok(std::iter::from_fn(move || {
+        if payload.is_empty() {
+            return None;
+        }
+        let mut rest = payload;
+        let err = match Header::decode(&mut rest) {
+            Ok(item) if item.payload_length <= rest.len() => {
+                let len = payload.len() - rest.len() + item.payload_length;
+                let (item, tail) = payload.split_at(len);
+                payload = tail;
+                return Some(Ok(item));
+            }
+            Ok(_) => MptError::InvalidNode,
+            Err(err) => err.into(),
+        };
+        // nothing after an item that does not decode can be trusted
+        payload = &[];
+        Some(Err(err))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{b256, hex};
+
+    use super::*;
+    use crate::mpt::{verify_account_proof, verify_storage_proof};
+
+    // state trie of twelve accounts 0x0101.., 0x0202.., .., 0x0c0c.. where account n has
+    // nonce n and balance 1000 * n; 0x0101.. also stores 7 and 8 in slots 0 and 1
+    const STATE_ROOT: B256 =
+        b256!("437145f4246d36c1e45429a5005f3908e1e2cc01976d7790b79c92f7e899737d");
+
+    fn account_1() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f90131a08a679c692ed331f19470c28c0740bff7eab24f060466028167715f3399ca3033a008bb5787951cf3e0f7ef37af538c189cf7a9c44289203d9eba075d9a1948198580a03940d75b19c21705e37e76ea253361b2a5166bb1220c43cd681275ae1b913e348080a09aaa39e9c7d98dd9d63f1a9e18023ac79bcf5e90e49da57051755e4b4815487ea0234234934946633a3d89fc26d7608a4f2ae151df98786860f1758a2361f3532d80a0be849c0554f24397f82f6e15462fde755e86a476fb86e52dc239ae56276a812ca0c8bc5b42ee87ba5356c539425709d265a2c31e2188239be656978aa5f1b814dba0358e80b96f3e60f45d36742c5e1a8f753688113df5e5e5cff54777d193f53c4e8080a09c9163790c43678142faec3e604398a7c9d27a36991291961aea89f9495df16b8080")),
+        Bytes::from(hex!("f851808080808080808080a0e22c30038a31da160f86b4bc348c319fbf12630550795fd3c05ab9dd3d4acfb48080a0190298bd2aa9642a5e867cba7ac01747ea1037e7ba3765c5782df2bc8b2a818980808080")),
+        Bytes::from(hex!("f86ba020654a4d194318e8ef7e64c6cbc31c341c650a6a039ea448faf8101af403da4db848f846018203e8a003b143323a8e054288efed5f34c7759b46b29309f69404ae6a49319c4442422ea01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+        ]
+    }
+
+    fn account_2() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f90131a08a679c692ed331f19470c28c0740bff7eab24f060466028167715f3399ca3033a008bb5787951cf3e0f7ef37af538c189cf7a9c44289203d9eba075d9a1948198580a03940d75b19c21705e37e76ea253361b2a5166bb1220c43cd681275ae1b913e348080a09aaa39e9c7d98dd9d63f1a9e18023ac79bcf5e90e49da57051755e4b4815487ea0234234934946633a3d89fc26d7608a4f2ae151df98786860f1758a2361f3532d80a0be849c0554f24397f82f6e15462fde755e86a476fb86e52dc239ae56276a812ca0c8bc5b42ee87ba5356c539425709d265a2c31e2188239be656978aa5f1b814dba0358e80b96f3e60f45d36742c5e1a8f753688113df5e5e5cff54777d193f53c4e8080a09c9163790c43678142faec3e604398a7c9d27a36991291961aea89f9495df16b8080")),
+        Bytes::from(hex!("f86ba03b40727044881a0015f3d04d723757bf0fd40eac11565ede1640f7fd76410e93b848f846028207d0a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+        ]
+    }
+
+    fn account_3() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f90131a08a679c692ed331f19470c28c0740bff7eab24f060466028167715f3399ca3033a008bb5787951cf3e0f7ef37af538c189cf7a9c44289203d9eba075d9a1948198580a03940d75b19c21705e37e76ea253361b2a5166bb1220c43cd681275ae1b913e348080a09aaa39e9c7d98dd9d63f1a9e18023ac79bcf5e90e49da57051755e4b4815487ea0234234934946633a3d89fc26d7608a4f2ae151df98786860f1758a2361f3532d80a0be849c0554f24397f82f6e15462fde755e86a476fb86e52dc239ae56276a812ca0c8bc5b42ee87ba5356c539425709d265a2c31e2188239be656978aa5f1b814dba0358e80b96f3e60f45d36742c5e1a8f753688113df5e5e5cff54777d193f53c4e8080a09c9163790c43678142faec3e604398a7c9d27a36991291961aea89f9495df16b8080")),
+        Bytes::from(hex!("e212a0808c0ae4cae52e104eed26d34bff649518023518b50f67ca77cb043ba71590d4")),
+        Bytes::from(hex!("f851808080808080a0be0b9c98f14cf1adaf7c65f9274a24579c143dbc8dac81b3b9562875562080348080808080a0fa5b383e6e2ce16c11aba683826e65803b536e7389c5398565a5971ec057d47b80808080")),
+        Bytes::from(hex!("f86a9f32f498f37adab9c7a4bf0aae161bb929b33867f5b5976848450005f577b8cbb848f84603820bb8a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+        ]
+    }
+
+    fn absent() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f90131a08a679c692ed331f19470c28c0740bff7eab24f060466028167715f3399ca3033a008bb5787951cf3e0f7ef37af538c189cf7a9c44289203d9eba075d9a1948198580a03940d75b19c21705e37e76ea253361b2a5166bb1220c43cd681275ae1b913e348080a09aaa39e9c7d98dd9d63f1a9e18023ac79bcf5e90e49da57051755e4b4815487ea0234234934946633a3d89fc26d7608a4f2ae151df98786860f1758a2361f3532d80a0be849c0554f24397f82f6e15462fde755e86a476fb86e52dc239ae56276a812ca0c8bc5b42ee87ba5356c539425709d265a2c31e2188239be656978aa5f1b814dba0358e80b96f3e60f45d36742c5e1a8f753688113df5e5e5cff54777d193f53c4e8080a09c9163790c43678142faec3e604398a7c9d27a36991291961aea89f9495df16b8080")),
+        Bytes::from(hex!("e212a0808c0ae4cae52e104eed26d34bff649518023518b50f67ca77cb043ba71590d4")),
+        ]
+    }
+
+    fn slot_0() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f8518080a03f4fce8c1dc82c0fcbdedc957a3563511bbf34669ec5418389c62821fb6392348080808080808080a03aa019bf75a24576f202168f0b02d6b7f71d86997ada3558a4b9267ab5324fdd8080808080")),
+        Bytes::from(hex!("e2a0390decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e56307")),
+        ]
+    }
+
+    fn slot_1() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f8518080a03f4fce8c1dc82c0fcbdedc957a3563511bbf34669ec5418389c62821fb6392348080808080808080a03aa019bf75a24576f202168f0b02d6b7f71d86997ada3558a4b9267ab5324fdd8080808080")),
+        Bytes::from(hex!("e2a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf608")),
+        ]
+    }
+
+    #[test]
+    fn test_matches_alloy_verifier() {
+        let proofs = [account_1(), account_2(), account_3(), absent()];
+        let addresses = [1, 2, 3, 0xee].map(Address::repeat_byte);
+
+        let mut verifier = CachedVerifier::new(STATE_ROOT);
+        for (address, proof) in addresses.iter().zip(&proofs) {
+            assert_eq!(
+                verifier.account(*address, proof).unwrap(),
+                verify_account_proof(*address, STATE_ROOT, proof).unwrap()
+            );
+        }
+        assert_eq!(verifier.account(addresses[3], &proofs[3]).unwrap(), None);
+        // 11 nodes in total, but the root is shared by all four proofs and the extension
+        // node by the proofs of 0x0303.. and 0xeeee..
+        assert_eq!(verifier.hashed_nodes(), 11 - 3 - 1);
+
+        let storage_root = verifier
+            .account(addresses[0], &proofs[0])
+            .unwrap()
+            .unwrap()
+            .storage_root;
+        let (slot_0, slot_1) = (slot_0(), slot_1());
+        let mut storage = CachedVerifier::new(storage_root);
+        for (slot, proof) in [(0u64, &slot_0), (1, &slot_1)] {
+            let slot = B256::from(U256::from(slot));
+            assert_eq!(
+                storage.storage(slot, proof).unwrap(),
+                verify_storage_proof(storage_root, slot, proof).unwrap()
+            );
+        }
+        assert_eq!(storage.hashed_nodes(), 3);
+    }
+
+    #[test]
+    fn test_invalid_proofs() {
+        let address = Address::repeat_byte(1);
+        let proof = account_1();
+
+        let mut verifier = CachedVerifier::new(STATE_ROOT);
+        assert!(matches!(
+            verifier.account(address, &proof[..1]),
+            Err(MptError::MissingNode(_))
+        ));
+
+        // a node cached under its hash does not vouch for different bytes
+        let mut tampered = proof.clone();
+        tampered[1] = Bytes::from(hex!("c0"));
+        assert!(matches!(
+            verifier.account(address, &tampered),
+            Err(MptError::HashMismatch(_))
+        ));
+
+        assert!(matches!(
+            CachedVerifier::new(B256::ZERO).account(address, &proof),
+            Err(MptError::HashMismatch(B256::ZERO))
+        ));
+        assert_eq!(
+            CachedVerifier::new(EMPTY_ROOT_HASH)
+                .account(address, &[])
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_malformed_nodes() {
+        let verify = |node: &[u8]| {
+            CachedVerifier::new(keccak256(node))
+                .verify(&[0x01], &[Bytes::copy_from_slice(node)])
+                .map(|value| value.map(<[u8]>::to_vec))
+        };
+        // a leaf for key 0x01 followed by a non-canonical item, which used to end the list
+        assert!(matches!(
+            verify(&hex!("c9822001" "82abcd" "b80105")),
+            Err(MptError::Rlp(_))
+        ));
+        // an item running past the end of the list
+        assert!(matches!(
+            verify(&hex!("c28201")),
+            Err(MptError::Rlp(alloy_rlp::Error::InputTooShort))
+        ));
+        // bytes after the list
+        assert!(matches!(
+            verify(&hex!("c0" "00")),
+            Err(MptError::InvalidNode)
+        ));
+    }
+}
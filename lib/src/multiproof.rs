@@ -0,0 +1,279 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::Decodable;
+use alloy_trie::nodes::TrieNode;
+use reth_primitives::EMPTY_ROOT_HASH;
+use reth_trie::Nibbles;
+use serde::{Deserialize, Serialize};
+
+use crate::{account::HdpAccount, rlp::Account, storage::HdpStorage};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MultiProofError {
+    #[error("node stored under {0} has a different hash")]
+    HashMismatch(B256),
+
+    #[error("node {0} is not in the multiproof")]
+    MissingNode(B256),
+
+    #[error("branch node holds a value, which state and storage tries never do")]
+    BranchValue,
+
+    #[error("invalid trie node: {0}")]
+    Rlp(#[from] alloy_rlp::Error),
+}
+
+/// Trie nodes of many proofs from one block, each stored once and keyed by its hash.
+/// Nodes of the state trie and of any storage trie can share one container.
+///
+/// Library-only for now: the guest input still carries every proof in full, so its stdin
+/// repeats the upper nodes shared between proofs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Eq)]
+pub struct MultiProof {
+    pub nodes: BTreeMap<B256, Bytes>,
+}
+
+impl MultiProof {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the nodes of one `eth_getProof`-style proof, skipping nodes already present.
+    pub fn add_proof(&mut self, proof: &[Bytes]) {
+        for node in proof {
+            self.nodes
+                .entry(keccak256(node))
+                .or_insert_with(|| node.clone());
+        }
+    }
+
+    /// Collects the proofs of `accounts` and `storages` at `block_number`.
+    pub fn from_block(block_number: u64, accounts: &[HdpAccount], storages: &[HdpStorage]) -> Self {
+        let mut multiproof = Self::new();
+        let proofs = accounts
+            .iter()
+            .filter_map(|account| account.proof_at(block_number))
+            .chain(
+                storages
+                    .iter()
+                    .filter_map(|storage| storage.proof_at(block_number)),
+            );
+        for proof in proofs {
+            multiproof.add_proof(proof);
+        }
+        multiproof
+    }
+
+    /// Checks every node against its key, hashing each node exactly once. Lookups through
+    /// the returned verifier only follow hashes and never hash again.
+    pub fn verifier(&self) -> Result<MultiProofVerifier<'_>, MultiProofError> {
+        let mut nodes = HashMap::with_capacity(self.nodes.len());
+        for (hash, node) in &self.nodes {
+            if keccak256(node) != *hash {
+                return Err(MultiProofError::HashMismatch(*hash));
+            }
+            nodes.insert(*hash, node.as_ref());
+        }
+        Ok(MultiProofVerifier { nodes })
+    }
+}
+
+/// Hash-checked nodes of a [`MultiProof`].
+#[derive(Debug, Clone)]
+pub struct MultiProofVerifier<'a> {
+    nodes: HashMap<B256, &'a [u8]>,
+}
+
+impl<'a> MultiProofVerifier<'a> {
+    /// Walks `key` from `root` and returns the leaf value, or `None` if the nodes prove the
+    /// key absent. Fails if the walk needs a node that is not in the multiproof.
+    pub fn get(&self, root: B256, key: &[u8]) -> Result<Option<Vec<u8>>, MultiProofError> {
+        if root == EMPTY_ROOT_HASH {
+            return Ok(None);
+        }
+        let path = Nibbles::unpack(key);
+        let mut depth = 0;
+        let mut node = Cow::Borrowed(self.node(root)?);
+
+        loop {
+            let child = match TrieNode::decode(&mut node.as_ref())? {
+                TrieNode::EmptyRoot => return Ok(None),
+                TrieNode::Branch(branch) => {
+                    let Some(&nibble) = path.get(depth) else {
+                        return Err(MultiProofError::BranchValue);
+                    };
+                    if !branch.state_mask.is_bit_set(nibble) {
+                        return Ok(None);
+                    }
+                    let index = (branch.state_mask.get() & ((1 << nibble) - 1)).count_ones();
+                    depth += 1;
+                    branch.stack[index as usize].clone()
+                }
+                TrieNode::Extension(extension) => {
+                    if !path[depth..].starts_with(&extension.key) {
+                        return Ok(None);
+                    }
+                    depth += extension.key.len();
+                    extension.child
+                }
+                TrieNode::Leaf(leaf) => {
+                    return Ok((path[depth..] == leaf.key[..]).then_some(leaf.value));
+                }
+            };
+
+            // children of 32 bytes or more are referenced by hash, smaller ones are inlined
+            node = if child.len() == B256::len_bytes() + 1 {
+                Cow::Borrowed(self.node(B256::from_slice(&child[1..]))?)
+            } else {
+                Cow::Owned(child)
+            };
+        }
+    }
+
+    /// The account at `address`, or `None` if it is proven absent.
+    pub fn account(
+        &self,
+        state_root: B256,
+        address: Address,
+    ) -> Result<Option<Account>, MultiProofError> {
+        self.get(state_root, keccak256(address).as_slice())?
+            .map(|value| Account::decode(&mut value.as_slice()))
+            .transpose()
+            .map_err(MultiProofError::from)
+    }
+
+    /// The value of `slot`, zero if it is proven absent.
+    pub fn storage(&self, storage_root: B256, slot: B256) -> Result<U256, MultiProofError> {
+        self.get(storage_root, keccak256(slot).as_slice())?
+            .map(|value| U256::decode(&mut value.as_slice()))
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(MultiProofError::from)
+    }
+
+    fn node(&self, hash: B256) -> Result<&'a [u8], MultiProofError> {
+        self.nodes
+            .get(&hash)
+            .copied()
+            .ok_or(MultiProofError::MissingNode(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{FixtureAccount, StateBuilder, StateFixture};
+
+    // twelve accounts 0x0101.., 0x0202.., .., 0x0c0c.. where account n has nonce n and
+    // balance 1000 * n; 0x0101.. also stores 7 and 8 in slots 0 and 1
+    fn state() -> StateFixture {
+        let mut builder = StateBuilder::new();
+        for n in 1..=12u8 {
+            let storage = match n {
+                1 => BTreeMap::from([(slot(0), U256::from(7)), (slot(1), U256::from(8))]),
+                _ => BTreeMap::new(),
+            };
+            builder = builder.account(
+                Address::repeat_byte(n),
+                FixtureAccount {
+                    nonce: n as u64,
+                    balance: U256::from(1000 * n as u64),
+                    storage,
+                    ..Default::default()
+                },
+            );
+        }
+        builder.build(1)
+    }
+
+    fn slot(n: u64) -> B256 {
+        B256::from(U256::from(n))
+    }
+
+    fn account_proof(state: &StateFixture, n: u8) -> Vec<Bytes> {
+        state.account_proof(Address::repeat_byte(n)).proofs[0]
+            .proof
+            .clone()
+    }
+
+    fn storage_proof(state: &StateFixture, n: u64) -> Vec<Bytes> {
+        state.storage_proof(Address::repeat_byte(1), slot(n)).proofs[0]
+            .proof
+            .clone()
+    }
+
+    #[test]
+    fn test_shared_nodes() {
+        let state = state();
+        let account_proofs: Vec<_> = [1, 2, 3, 4, 0xee]
+            .into_iter()
+            .map(|n| account_proof(&state, n))
+            .collect();
+        let mut multiproof = MultiProof::new();
+        for proof in account_proofs
+            .iter()
+            .chain(&[storage_proof(&state, 0), storage_proof(&state, 1)])
+        {
+            multiproof.add_proof(proof);
+        }
+
+        let total: usize = account_proofs.iter().map(Vec::len).sum();
+        // the root is shared by all five account proofs and the extension below it by two,
+        // leaving 8 unique account nodes; the two storage proofs share their root
+        assert_eq!(total, 13);
+        assert_eq!(multiproof.nodes.len(), 8 + 3);
+
+        let state_root = state.state_root();
+        let verifier = multiproof.verifier().unwrap();
+        for n in 1..=4u8 {
+            let account = verifier
+                .account(state_root, Address::repeat_byte(n))
+                .unwrap()
+                .unwrap();
+            assert_eq!(account.nonce, n as u64);
+            assert_eq!(account.balance, U256::from(1000 * n as u64));
+        }
+        assert_eq!(
+            verifier
+                .account(state_root, Address::repeat_byte(0xee))
+                .unwrap(),
+            None
+        );
+
+        let storage_root = state.account(Address::repeat_byte(1)).unwrap().storage_root;
+        assert_eq!(
+            verifier.storage(storage_root, slot(0)).unwrap(),
+            U256::from(7)
+        );
+        assert_eq!(
+            verifier.storage(storage_root, slot(1)).unwrap(),
+            U256::from(8)
+        );
+        assert_eq!(verifier.storage(storage_root, slot(2)).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_missing_and_tampered_nodes() {
+        let state = state();
+        let mut multiproof = MultiProof::new();
+        multiproof.add_proof(&account_proof(&state, 1));
+
+        // account 5 is in the trie, but its path leaves the nodes of account 1's proof
+        let verifier = multiproof.verifier().unwrap();
+        assert!(matches!(
+            verifier.account(state.state_root(), Address::repeat_byte(5)),
+            Err(MultiProofError::MissingNode(_))
+        ));
+
+        let (&hash, _) = multiproof.nodes.iter().next().unwrap();
+        multiproof.nodes.insert(hash, Bytes::from_static(&[0xc0]));
+        assert!(matches!(
+            multiproof.verifier(),
+            Err(MultiProofError::HashMismatch(_))
+        ));
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::hash::HashId;
+
+/// Result of verifying a guest input. It is part of the public values, so an invalid
+/// input still proves why it was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum Outcome {
+    Valid,
+
+    /// The input was built for the MMR hasher the program was not built with.
+    HashIdMismatch {
+        expected: HashId,
+        got: HashId,
+    },
+
+    /// The peaks do not bag to the MMR root.
+    MmrRootMismatch,
+
+    /// The inclusion proof of header `index` does not fit the MMR size and peaks.
+    BadProofShape {
+        index: usize,
+    },
+
+    /// Header `index` is not the leaf its inclusion proof points at.
+    HeaderNotInMmr {
+        index: usize,
+    },
+
+    HeaderDecodeError {
+        index: usize,
+    },
+
+    /// The proof at `block_number` of account `index` does not prove the account against
+    /// that block's state root.
+    AccountProofFailure {
+        index: usize,
+        block_number: u64,
+    },
+
+    /// The proof at `block_number` of storage `index` does not prove the slot against the
+    /// account's storage root at that block.
+    StorageProofFailure {
+        index: usize,
+        block_number: u64,
+    },
+
+    /// A proof is for `block_number`, which none of the verified headers is.
+    BlockNumberMismatch {
+        block_number: u64,
+    },
+
+    /// Diff `index` could not be proven between its two headers.
+    DiffProofFailure {
+        index: usize,
+    },
+}
+
+impl Outcome {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+
+    /// Flat form used in the ABI encoding: a code in declaration order, the index and the
+    /// block number of variants that have them, zero otherwise.
+    pub fn to_parts(&self) -> (u8, u64, u64) {
+        match *self {
+            Self::Valid => (0, 0, 0),
+            Self::HashIdMismatch { .. } => (1, 0, 0),
+            Self::MmrRootMismatch => (2, 0, 0),
+            Self::BadProofShape { index } => (3, index as u64, 0),
+            Self::HeaderNotInMmr { index } => (4, index as u64, 0),
+            Self::HeaderDecodeError { index } => (5, index as u64, 0),
+            Self::AccountProofFailure {
+                index,
+                block_number,
+            } => (6, index as u64, block_number),
+            Self::StorageProofFailure {
+                index,
+                block_number,
+            } => (7, index as u64, block_number),
+            Self::BlockNumberMismatch { block_number } => (8, 0, block_number),
+            Self::DiffProofFailure { index } => (9, index as u64, 0),
+        }
+    }
+
+    /// Inverse of [`Outcome::to_parts`] for a program built with `hash_id`, which is the
+    /// expected side of a `HashIdMismatch`.
+    pub fn from_parts(code: u8, index: u64, block_number: u64, hash_id: HashId) -> Option<Self> {
+        let index = index as usize;
+        Some(match code {
+            0 => Self::Valid,
+            1 => Self::HashIdMismatch {
+                expected: hash_id,
+                got: match hash_id {
+                    HashId::Keccak => HashId::Poseidon,
+                    HashId::Poseidon => HashId::Keccak,
+                },
+            },
+            2 => Self::MmrRootMismatch,
+            3 => Self::BadProofShape { index },
+            4 => Self::HeaderNotInMmr { index },
+            5 => Self::HeaderDecodeError { index },
+            6 => Self::AccountProofFailure {
+                index,
+                block_number,
+            },
+            7 => Self::StorageProofFailure {
+                index,
+                block_number,
+            },
+            8 => Self::BlockNumberMismatch { block_number },
+            9 => Self::DiffProofFailure { index },
+            _ => return None,
+        })
+    }
+}
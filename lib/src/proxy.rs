@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{b256, Address, B256, U256};
+
+use crate::{
+    account::HdpAccount,
+    mpt::{verify_storage, MptError},
+    storage::HdpStorage,
+    storage_value::{decode_storage, StorageDecodeError, StorageType, StorageValue},
+};
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+pub const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.admin")) - 1)`
+pub const ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`
+pub const BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// `_implementation` of OpenZeppelin's `UpgradeableBeacon`, stored after `Ownable._owner`.
+pub const OZ_BEACON_IMPLEMENTATION_SLOT: B256 =
+    b256!("0000000000000000000000000000000000000000000000000000000000000001");
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProxyError {
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error(transparent)]
+    Decode(#[from] StorageDecodeError),
+
+    #[error("{0} has no beacon")]
+    NotABeaconProxy(Address),
+
+    #[error("proxy beacon is {expected}, beacon proof is for {got}")]
+    BeaconMismatch { expected: Address, got: Address },
+}
+
+/// Addresses held in a proxy's EIP-1967 slots. Empty slots are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxySlots {
+    pub proxy: Address,
+    /// The proxy's own implementation slot, or the beacon's implementation when a beacon
+    /// proof was followed.
+    pub implementation: Option<Address>,
+    pub admin: Option<Address>,
+    pub beacon: Option<Address>,
+}
+
+/// Account and storage proofs of a beacon contract, proving its implementation slot.
+#[derive(Debug, Clone, Copy)]
+pub struct BeaconProof<'a> {
+    pub account: &'a HdpAccount,
+    pub storages: &'a [HdpStorage],
+    pub implementation_slot: B256,
+}
+
+/// Proves the EIP-1967 implementation, admin and beacon slots of `proxy` at `block_number`.
+/// `storages` must prove all three slots; empty slots are proven by exclusion proofs.
+/// If `beacon` is given, the proxy's beacon is followed to its implementation using the
+/// beacon's proofs from the same block.
+pub fn resolve_proxy(
+    state_root: B256,
+    block_number: u64,
+    proxy: &HdpAccount,
+    storages: &[HdpStorage],
+    beacon: Option<BeaconProof>,
+) -> Result<ProxySlots, ProxyError> {
+    let slots = verify_storage(state_root, block_number, proxy, storages)?;
+    let mut resolved = ProxySlots {
+        proxy: proxy.address,
+        implementation: read_address(&slots, IMPLEMENTATION_SLOT)?,
+        admin: read_address(&slots, ADMIN_SLOT)?,
+        beacon: read_address(&slots, BEACON_SLOT)?,
+    };
+
+    if let Some(beacon) = beacon {
+        let expected = resolved
+            .beacon
+            .ok_or(ProxyError::NotABeaconProxy(proxy.address))?;
+        if beacon.account.address != expected {
+            return Err(ProxyError::BeaconMismatch {
+                expected,
+                got: beacon.account.address,
+            });
+        }
+        let slots = verify_storage(state_root, block_number, beacon.account, beacon.storages)?;
+        resolved.implementation = read_address(&slots, beacon.implementation_slot)?;
+    }
+
+    Ok(resolved)
+}
+
+fn read_address(slots: &HashMap<B256, U256>, slot: B256) -> Result<Option<Address>, ProxyError> {
+    match decode_storage(slots, slot, 0, StorageType::Address)? {
+        StorageValue::Address(address) if !address.is_zero() => Ok(Some(address)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::keccak256;
+    use reth_primitives::{address, hex, Bytes};
+
+    use super::*;
+    use crate::account::ProcessedMPTProof;
+
+    // state trie of a transparent proxy (0x11..), a beacon proxy (0x22..) and an
+    // `UpgradeableBeacon` (0x33..), all pointing at implementation 0x44..
+    const STATE_ROOT: B256 =
+        b256!("6527c067d8e963e417aaa4232f1dc632ab61c577312cb5b76c1a69d70f5a4e80");
+    const BLOCK: u64 = 20000000;
+    const IMPLEMENTATION: Address = address!("4444444444444444444444444444444444444444");
+
+    fn storage(address: Address, slot: B256, proof: Vec<Bytes>) -> HdpStorage {
+        HdpStorage {
+            address,
+            slot,
+            storage_key: keccak256(slot),
+            proofs: vec![ProcessedMPTProof {
+                block_number: BLOCK,
+                proof,
+            }],
+            claims: vec![],
+        }
+    }
+
+    fn transparent_proxy() -> (HdpAccount, Vec<HdpStorage>) {
+        let address = address!("1111111111111111111111111111111111111111");
+        let account = HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![ProcessedMPTProof {
+                block_number: BLOCK,
+                proof: vec![
+                    Bytes::from(hex!("f8718080a08772655d4f8f15e6c3f7fec61e079b708d9480f3cb4c0b22860a401d4c34b04aa063544ee62661cf79229861db966c8cf599d200e5265e7344061815d8698281d280808080808080808080a015e5f5c52403fa268b875811ae785a2fd246c78ae449fc48b2298f3a426f213b8080")),
+                    Bytes::from(hex!("f869a032c07404b8c1df4c46226425cac68c28d27a766bbddce62309f36724839b22c0b846f8440180a0411cb19aa0d53ec137c041fdb312357faab19a819832f0e9121cb0c52e7d70cea01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                ],
+            }],
+            claims: vec![],
+        };
+        let storages = vec![
+            storage(
+                address,
+                IMPLEMENTATION_SLOT,
+                vec![
+                    Bytes::from(hex!("f8518080808080a046c31678bba0b5e8dc0aafd7c6b2754afe919131c0c0b555a107bae94fa22b1480a06319d15a0b566ef438e4d30da3594d929bd9042bea0ce52c832d1fa77e3e23fc808080808080808080")),
+                    Bytes::from(hex!("f7a035b20eef8615de99c108b05f0dbda081c91897128caa336d75dffb97c4132b4d95944444444444444444444444444444444444444444")),
+                ],
+            ),
+            storage(
+                address,
+                ADMIN_SLOT,
+                vec![
+                    Bytes::from(hex!("f8518080808080a046c31678bba0b5e8dc0aafd7c6b2754afe919131c0c0b555a107bae94fa22b1480a06319d15a0b566ef438e4d30da3594d929bd9042bea0ce52c832d1fa77e3e23fc808080808080808080")),
+                    Bytes::from(hex!("f7a032df0bdf5a5f92d8037cf11e50f13d8017aefc99d20a73c826416df79570d48195945555555555555555555555555555555555555555")),
+                ],
+            ),
+            storage(
+                address,
+                BEACON_SLOT,
+                vec![
+                    Bytes::from(hex!("f8518080808080a046c31678bba0b5e8dc0aafd7c6b2754afe919131c0c0b555a107bae94fa22b1480a06319d15a0b566ef438e4d30da3594d929bd9042bea0ce52c832d1fa77e3e23fc808080808080808080")),
+                ],
+            ),
+        ];
+        (account, storages)
+    }
+
+    fn beacon_proxy() -> (HdpAccount, Vec<HdpStorage>) {
+        let address = address!("2222222222222222222222222222222222222222");
+        let account = HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![ProcessedMPTProof {
+                block_number: BLOCK,
+                proof: vec![
+                    Bytes::from(hex!("f8718080a08772655d4f8f15e6c3f7fec61e079b708d9480f3cb4c0b22860a401d4c34b04aa063544ee62661cf79229861db966c8cf599d200e5265e7344061815d8698281d280808080808080808080a015e5f5c52403fa268b875811ae785a2fd246c78ae449fc48b2298f3a426f213b8080")),
+                    Bytes::from(hex!("f869a03ab0a4443bbea3fbe4d0e1503d11ff1367842fb0c8b28a5c8550f27599a40751b846f8440180a065315195a3ab246bbdcd7768cf14eef6a639a93ec5cad21509d4d534e94bc127a01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                ],
+            }],
+            claims: vec![],
+        };
+        let storages = vec![
+            storage(
+                address,
+                IMPLEMENTATION_SLOT,
+                vec![
+                    Bytes::from(hex!("f838a12048bf63a2fc0cedde9effb3f5b74f519e781a8da221ed8e3253206f2b0a0d2f1f95943333333333333333333333333333333333333333")),
+                ],
+            ),
+            storage(
+                address,
+                ADMIN_SLOT,
+                vec![
+                    Bytes::from(hex!("f838a12048bf63a2fc0cedde9effb3f5b74f519e781a8da221ed8e3253206f2b0a0d2f1f95943333333333333333333333333333333333333333")),
+                ],
+            ),
+            storage(
+                address,
+                BEACON_SLOT,
+                vec![
+                    Bytes::from(hex!("f838a12048bf63a2fc0cedde9effb3f5b74f519e781a8da221ed8e3253206f2b0a0d2f1f95943333333333333333333333333333333333333333")),
+                ],
+            ),
+        ];
+        (account, storages)
+    }
+
+    fn beacon() -> (HdpAccount, Vec<HdpStorage>) {
+        let address = address!("3333333333333333333333333333333333333333");
+        let account = HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![ProcessedMPTProof {
+                block_number: BLOCK,
+                proof: vec![
+                    Bytes::from(hex!("f8718080a08772655d4f8f15e6c3f7fec61e079b708d9480f3cb4c0b22860a401d4c34b04aa063544ee62661cf79229861db966c8cf599d200e5265e7344061815d8698281d280808080808080808080a015e5f5c52403fa268b875811ae785a2fd246c78ae449fc48b2298f3a426f213b8080")),
+                    Bytes::from(hex!("f869a037d95e0aa71e34defa88b4c43498bc8b90207e31ad0ef4aa6f5bea78bd25a1abb846f8440180a0b4209d44f4ff01e59b80f69252056ddebe99aec8ae013fcff64d7b4cf6328ebca01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+                ],
+            }],
+            claims: vec![],
+        };
+        let storages = vec![
+            storage(
+                address,
+                OZ_BEACON_IMPLEMENTATION_SLOT,
+                vec![
+                    Bytes::from(hex!("f8518080a02337f46a969e7d4a2a5a3e0e079150d669439ba31a74a93906fa130c1e1021a88080808080808080a0e6042ffa6fd9616b08ae472f2e06fc99915b0bf27007ee098bbb29ecd5a320a68080808080")),
+                    Bytes::from(hex!("f7a0310e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf695944444444444444444444444444444444444444444")),
+                ],
+            ),
+        ];
+        (account, storages)
+    }
+
+    #[test]
+    fn test_transparent_proxy() {
+        let (proxy, storages) = transparent_proxy();
+
+        let resolved = resolve_proxy(STATE_ROOT, BLOCK, &proxy, &storages, None).unwrap();
+        assert_eq!(
+            resolved,
+            ProxySlots {
+                proxy: proxy.address,
+                implementation: Some(IMPLEMENTATION),
+                admin: Some(address!("5555555555555555555555555555555555555555")),
+                beacon: None,
+            }
+        );
+
+        // the admin slot must be proven even when only the implementation is of interest
+        assert!(matches!(
+            resolve_proxy(STATE_ROOT, BLOCK, &proxy, &storages[..1], None),
+            Err(ProxyError::Decode(StorageDecodeError::MissingSlot(
+                ADMIN_SLOT
+            )))
+        ));
+        assert!(matches!(
+            resolve_proxy(STATE_ROOT, BLOCK + 1, &proxy, &storages, None),
+            Err(ProxyError::Mpt(MptError::MissingBlockProof(_)))
+        ));
+    }
+
+    #[test]
+    fn test_beacon_proxy() {
+        let (proxy, storages) = beacon_proxy();
+        let (beacon, beacon_storages) = beacon();
+        let beacon_proof = BeaconProof {
+            account: &beacon,
+            storages: &beacon_storages,
+            implementation_slot: OZ_BEACON_IMPLEMENTATION_SLOT,
+        };
+
+        let unresolved = resolve_proxy(STATE_ROOT, BLOCK, &proxy, &storages, None).unwrap();
+        assert_eq!(unresolved.implementation, None);
+        assert_eq!(unresolved.beacon, Some(beacon.address));
+
+        let resolved =
+            resolve_proxy(STATE_ROOT, BLOCK, &proxy, &storages, Some(beacon_proof)).unwrap();
+        assert_eq!(resolved.implementation, Some(IMPLEMENTATION));
+        assert_eq!(resolved.admin, None);
+
+        let (transparent, transparent_storages) = transparent_proxy();
+        assert!(matches!(
+            resolve_proxy(
+                STATE_ROOT,
+                BLOCK,
+                &transparent,
+                &transparent_storages,
+                Some(beacon_proof)
+            ),
+            Err(ProxyError::NotABeaconProxy(_))
+        ));
+    }
+
+    #[test]
+    fn test_beacon_mismatch() {
+        let (proxy, storages) = beacon_proxy();
+        let (transparent, transparent_storages) = transparent_proxy();
+
+        let wrong_beacon = BeaconProof {
+            account: &transparent,
+            storages: &transparent_storages,
+            implementation_slot: IMPLEMENTATION_SLOT,
+        };
+        assert!(matches!(
+            resolve_proxy(STATE_ROOT, BLOCK, &proxy, &storages, Some(wrong_beacon)),
+            Err(ProxyError::BeaconMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use alloy_consensus::ReceiptEnvelope;
+use alloy_eips::eip2718::{Decodable2718, Eip2718Error};
+use reth_primitives::Header;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::ProcessedMPTProof,
+    mpt::{verify_inclusion, MptError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReceiptError {
+    #[error("proof is for block {proof}, header is block {header}")]
+    BlockNumberMismatch { proof: u64, header: u64 },
+
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error("invalid receipt encoding: {0}")]
+    Decode(#[from] Eip2718Error),
+}
+
+/// Inclusion proof of the receipt at `tx_index` in the block's `receipts_root`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct HdpReceipt {
+    pub tx_index: u64,
+    pub proof: ProcessedMPTProof,
+}
+
+/// A receipt that has been verified against the `receipts_root` of block `block_number`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenReceipt {
+    pub block_number: u64,
+    pub tx_index: u64,
+    pub receipt: ReceiptEnvelope,
+}
+
+/// Decodes an EIP-2718 encoded receipt, as stored in the receipts trie.
+pub fn decode_receipt(encoded: &[u8]) -> Result<ReceiptEnvelope, ReceiptError> {
+    Ok(ReceiptEnvelope::decode_2718(&mut &encoded[..])?)
+}
+
+/// Verifies the receipt against the `receipts_root` of a verified header and decodes it.
+pub fn verify_receipt(
+    receipt: &HdpReceipt,
+    header: &Header,
+) -> Result<ProvenReceipt, ReceiptError> {
+    if receipt.proof.block_number != header.number {
+        return Err(ReceiptError::BlockNumberMismatch {
+            proof: receipt.proof.block_number,
+            header: header.number,
+        });
+    }
+
+    let key = alloy_rlp::encode(receipt.tx_index);
+    let encoded = verify_inclusion(header.receipts_root, &key, &receipt.proof.proof)?;
+
+    Ok(ProvenReceipt {
+        block_number: header.number,
+        tx_index: receipt.tx_index,
+        receipt: decode_receipt(&encoded)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::TxType;
+    use alloy_primitives::B256;
+    use reth_primitives::{address, b256, hex, Bytes};
+
+    use super::*;
+
+    const RECEIPTS_ROOT: B256 =
+        b256!("e84c91741ff225216fc7f55c838e56aa8a49b8a8f1ffeb24f79ac3a0a8133cf4");
+
+    fn header(receipts_root: B256) -> Header {
+        Header {
+            number: 5641516,
+            receipts_root,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_verify_legacy_receipt() {
+        let receipt = HdpReceipt {
+            tx_index: 0,
+            proof: ProcessedMPTProof {
+                block_number: 5641516,
+                proof: vec![
+                    Bytes::from(hex!("f851a09e6a3a7d69ec189fda2352e2dc6afd70cceb45243dd9aa97220ec424f818268680808080808080a0fac459ae95dab7257d2ef5717adfc5fe174c061be9261d7d2c8f505e5e4cda558080808080808080")),
+                    Bytes::from(hex!("f9024b30b90247f902440182c738b9010000000000000000000000000000000002000000000000000000000000000000000000202000000000000000000000000000080000000004000000800020200000000000000000000000000008000400000000000000000000000000000000000000000000080000000000000000000000000000008000000000000010000000000000000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000080000002000000000000000000000000000000000000000000000000000000000010000000000000000000000000000008000800100000800000000000000000f9013af89b947f2c6f930306d3aa736b3a6c6a98f512f74036d4f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000001a642f0e3c3af545e7acbd38b07251b3990914f1a00000000000000000000000002222222222222222222222222222222222222222a000000000000000000000000000000000000000000000000000000000000003e8f89b9475cec1db9dceb703200eaa6595f66885c962b920f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000001a642f0e3c3af545e7acbd38b07251b3990914f1a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000005")),
+                ],
+            },
+        };
+
+        let proven = verify_receipt(&receipt, &header(RECEIPTS_ROOT)).unwrap();
+        assert_eq!(proven.block_number, 5641516);
+        assert_eq!(proven.tx_index, 0);
+        assert_eq!(proven.receipt.tx_type(), TxType::Legacy);
+        assert!(proven.receipt.status());
+        assert_eq!(proven.receipt.cumulative_gas_used(), 51000);
+        assert_eq!(proven.receipt.logs().len(), 2);
+        assert_eq!(
+            proven.receipt.logs()[0].address,
+            address!("7f2c6f930306d3aa736b3a6c6a98f512f74036d4")
+        );
+    }
+
+    #[test]
+    fn test_verify_eip1559_receipt() {
+        let receipt = HdpReceipt {
+            tx_index: 1,
+            proof: ProcessedMPTProof {
+                block_number: 5641516,
+                proof: vec![
+                    Bytes::from(hex!("f851a09e6a3a7d69ec189fda2352e2dc6afd70cceb45243dd9aa97220ec424f818268680808080808080a0fac459ae95dab7257d2ef5717adfc5fe174c061be9261d7d2c8f505e5e4cda558080808080808080")),
+                    Bytes::from(hex!("f85180a07202dbe9bba41716d4b3bd3b77d457c303749d7030cebcbe6990b2f8f27cb5aea0a78cb3a33e1a871278589fcbd350b317c27aa703b08c37b4e6ea28df6f3e85d78080808080808080808080808080")),
+                    Bytes::from(hex!("f901af20b901ab02f901a70183018e70b9010000000000000000000000000000000002000000000000000000000000000000000000202000000000000000000000000000080000000004000000000020000000000000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080000002000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000800100000000000000000000000f89df89b947f2c6f930306d3aa736b3a6c6a98f512f74036d4f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000002222222222222222222222222222222222222222a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000190")),
+                ],
+            },
+        };
+
+        let proven = verify_receipt(&receipt, &header(RECEIPTS_ROOT)).unwrap();
+        assert_eq!(proven.receipt.tx_type(), TxType::Eip1559);
+        assert_eq!(proven.receipt.cumulative_gas_used(), 102000);
+        assert_eq!(proven.receipt.logs().len(), 1);
+
+        assert!(verify_receipt(&receipt, &header(B256::ZERO)).is_err());
+
+        let other_block = Header {
+            number: 5641517,
+            ..header(RECEIPTS_ROOT)
+        };
+        assert!(matches!(
+            verify_receipt(&receipt, &other_block),
+            Err(ReceiptError::BlockNumberMismatch {
+                proof: 5641516,
+                header: 5641517
+            })
+        ));
+    }
+}
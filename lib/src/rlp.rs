@@ -11,7 +11,20 @@ pub fn get_state_root(rlp_string: String) -> Result<B256, Box<dyn Error>> {
     Ok(decoded.state_root)
 }
 
-#[derive(Debug, RlpDecodable, RlpEncodable, PartialEq)]
+pub fn get_header(rlp_string: String) -> Result<Header, Box<dyn Error>> {
+    let rlp = hex::decode(rlp_string)?;
+    decode_header(&rlp)
+}
+
+pub fn decode_header(rlp: &[u8]) -> Result<Header, Box<dyn Error>> {
+    Ok(Header::decode(&mut &rlp[..])?)
+}
+
+pub fn encode_header(header: &Header) -> Vec<u8> {
+    alloy_rlp::encode(header)
+}
+
+#[derive(Debug, Clone, RlpDecodable, RlpEncodable, PartialEq)]
 pub struct Account {
     pub nonce: u64,
     pub balance: U256,
@@ -1,8 +1,11 @@
-use alloy_primitives::Address;
-use reth_primitives::{StorageKey, B256};
+use alloy_primitives::{Address, U256};
+use reth_primitives::{Bytes, StorageKey, B256};
 use serde::{Deserialize, Serialize};
 
-use crate::account::ProcessedMPTProof;
+use crate::{
+    account::{proof_at, ProcessedMPTProof},
+    storage_layout::StoragePath,
+};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
 pub struct HdpStorage {
@@ -10,4 +13,39 @@ pub struct HdpStorage {
     pub slot: B256,
     pub storage_key: StorageKey,
     pub proofs: Vec<ProcessedMPTProof>,
+    /// Values reported by the RPC alongside the proofs; unverified until checked against them.
+    #[serde(default)]
+    pub claims: Vec<StorageClaim>,
+}
+
+/// Slot value of an `eth_getProof` response at `block_number`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct StorageClaim {
+    pub block_number: u64,
+    pub value: U256,
+}
+
+impl HdpStorage {
+    /// Builds a storage request for the Solidity variable at `path`.
+    pub fn from_path(address: Address, path: &StoragePath, proofs: Vec<ProcessedMPTProof>) -> Self {
+        Self {
+            address,
+            slot: path.slot(),
+            storage_key: path.storage_key(),
+            proofs,
+            claims: vec![],
+        }
+    }
+
+    /// The storage proof for `block_number`, if one was supplied.
+    pub fn proof_at(&self, block_number: u64) -> Option<&[Bytes]> {
+        proof_at(&self.proofs, block_number)
+    }
+
+    /// The claimed slot value at `block_number`, if any.
+    pub fn claim_at(&self, block_number: u64) -> Option<&StorageClaim> {
+        self.claims
+            .iter()
+            .find(|claim| claim.block_number == block_number)
+    }
 }
@@ -0,0 +1,199 @@
+use alloy_primitives::{keccak256, Address, B256, U256};
+use serde::{Deserialize, Serialize};
+
+/// A value used to index a Solidity mapping.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum MappingKey {
+    Address(Address),
+    Uint(U256),
+    Bool(bool),
+    /// `bytes32` and other value types already left-padded to a word.
+    Word(B256),
+    /// `string` and `bytes` keys, which are hashed unpadded.
+    Bytes(Vec<u8>),
+}
+
+impl MappingKey {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            MappingKey::Address(address) => address.into_word().to_vec(),
+            MappingKey::Uint(value) => value.to_be_bytes::<32>().to_vec(),
+            MappingKey::Bool(value) => U256::from(*value as u8).to_be_bytes::<32>().to_vec(),
+            MappingKey::Word(word) => word.to_vec(),
+            MappingKey::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
+impl From<Address> for MappingKey {
+    fn from(value: Address) -> Self {
+        MappingKey::Address(value)
+    }
+}
+
+impl From<U256> for MappingKey {
+    fn from(value: U256) -> Self {
+        MappingKey::Uint(value)
+    }
+}
+
+impl From<B256> for MappingKey {
+    fn from(value: B256) -> Self {
+        MappingKey::Word(value)
+    }
+}
+
+/// One step of a storage path, applied to the slot computed so far.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum PathElement {
+    /// `m[key]`: `keccak256(key . slot)`.
+    Mapping(MappingKey),
+    /// `arr[index]` of a dynamic array: `keccak256(slot) + index * element_slots`.
+    DynamicIndex { index: U256, element_slots: U256 },
+    /// `arr[index]` of a fixed-size array: `slot + index * element_slots`.
+    FixedIndex { index: U256, element_slots: U256 },
+    /// Struct member at `offset` slots from the start of the struct.
+    Field(U256),
+}
+
+/// Typed path to a Solidity state variable, e.g. `allowance[a][b]` or `arr[i].field`.
+/// Elements are assumed to start on a slot boundary, which holds for mappings, structs and
+/// arrays whose elements take at least one full slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct StoragePath {
+    pub base_slot: U256,
+    pub elements: Vec<PathElement>,
+}
+
+impl StoragePath {
+    pub fn new(base_slot: U256) -> Self {
+        Self {
+            base_slot,
+            elements: vec![],
+        }
+    }
+
+    pub fn mapping(mut self, key: impl Into<MappingKey>) -> Self {
+        self.elements.push(PathElement::Mapping(key.into()));
+        self
+    }
+
+    pub fn dynamic_index(mut self, index: U256, element_slots: U256) -> Self {
+        self.elements.push(PathElement::DynamicIndex {
+            index,
+            element_slots,
+        });
+        self
+    }
+
+    pub fn fixed_index(mut self, index: U256, element_slots: U256) -> Self {
+        self.elements.push(PathElement::FixedIndex {
+            index,
+            element_slots,
+        });
+        self
+    }
+
+    pub fn field(mut self, offset: U256) -> Self {
+        self.elements.push(PathElement::Field(offset));
+        self
+    }
+
+    /// The storage slot the path resolves to.
+    pub fn slot(&self) -> B256 {
+        let slot = self
+            .elements
+            .iter()
+            .fold(self.base_slot, |slot, element| match element {
+                PathElement::Mapping(key) => {
+                    let mut preimage = key.encode();
+                    preimage.extend_from_slice(&slot.to_be_bytes::<32>());
+                    keccak256(preimage).into()
+                }
+                PathElement::DynamicIndex {
+                    index,
+                    element_slots,
+                } => {
+                    let base: U256 = keccak256(slot.to_be_bytes::<32>()).into();
+                    base.wrapping_add(index.wrapping_mul(*element_slots))
+                }
+                PathElement::FixedIndex {
+                    index,
+                    element_slots,
+                } => slot.wrapping_add(index.wrapping_mul(*element_slots)),
+                PathElement::Field(offset) => slot.wrapping_add(*offset),
+            });
+        slot.into()
+    }
+
+    /// The key of the slot in the account's storage trie, `keccak256(slot)`.
+    pub fn storage_key(&self) -> B256 {
+        keccak256(self.slot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, b256};
+
+    use super::*;
+
+    #[test]
+    fn test_plain_variable() {
+        let path = StoragePath::new(U256::from(1));
+
+        assert_eq!(path.slot(), B256::from(U256::from(1)));
+        // matches the `storage_key` of the keccak fixture
+        assert_eq!(
+            path.storage_key(),
+            b256!("b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6")
+        );
+    }
+
+    #[test]
+    fn test_mappings() {
+        let owner = address!("1a642f0e3c3af545e7acbd38b07251b3990914f1");
+        let spender = address!("75cec1db9dceb703200eaa6595f66885c962b920");
+
+        // balances[owner], balances at slot 0
+        let balance = StoragePath::new(U256::ZERO).mapping(owner);
+        let mut preimage = owner.into_word().to_vec();
+        preimage.extend_from_slice(&[0u8; 32]);
+        assert_eq!(balance.slot(), keccak256(&preimage));
+
+        // allowance[owner][spender], allowance at slot 1
+        let allowance = StoragePath::new(U256::from(1))
+            .mapping(owner)
+            .mapping(spender);
+        let mut inner = owner.into_word().to_vec();
+        inner.extend_from_slice(&B256::from(U256::from(1)).0);
+        let mut outer = spender.into_word().to_vec();
+        outer.extend_from_slice(&keccak256(&inner).0);
+        assert_eq!(allowance.slot(), keccak256(&outer));
+
+        // string keys are hashed unpadded
+        let named = StoragePath::new(U256::from(2)).mapping(MappingKey::Bytes(b"hdp".to_vec()));
+        let mut preimage = b"hdp".to_vec();
+        preimage.extend_from_slice(&B256::from(U256::from(2)).0);
+        assert_eq!(named.slot(), keccak256(&preimage));
+    }
+
+    #[test]
+    fn test_arrays_and_structs() {
+        // keccak256(uint256(0)), the data slot of a dynamic array at slot 0
+        let data = U256::from_be_bytes(
+            b256!("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563").0,
+        );
+
+        // arr[3].field where arr is a dynamic array of 2-slot structs at slot 0,
+        // and field is the second member
+        let path = StoragePath::new(U256::ZERO)
+            .dynamic_index(U256::from(3), U256::from(2))
+            .field(U256::from(1));
+        assert_eq!(path.slot(), B256::from(data + U256::from(7)));
+
+        // fixed[2] of a uint256[4] declared after two single-slot variables
+        let fixed = StoragePath::new(U256::from(2)).fixed_index(U256::from(2), U256::from(1));
+        assert_eq!(fixed.slot(), B256::from(U256::from(4)));
+    }
+}
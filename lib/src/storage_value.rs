@@ -0,0 +1,299 @@
+use std::{collections::HashMap, iter};
+
+use alloy_primitives::{keccak256, Address, B256, I256, U256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageDecodeError {
+    #[error("slot {0} was not proven")]
+    MissingSlot(B256),
+
+    #[error("{size} byte value does not fit at offset {offset}")]
+    InvalidOffset { size: usize, offset: usize },
+
+    #[error("unsupported type size {0}")]
+    InvalidSize(usize),
+
+    #[error("string/bytes length encoding is malformed")]
+    InvalidLength,
+
+    #[error("string is not valid utf-8")]
+    InvalidUtf8,
+}
+
+/// Solidity type of a storage variable.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum StorageType {
+    /// `uintN`, with `N` in bits.
+    Uint(usize),
+    /// `intN`, with `N` in bits.
+    Int(usize),
+    Address,
+    Bool,
+    /// `bytesN`, with `N` in bytes.
+    FixedBytes(usize),
+    String,
+    Bytes,
+}
+
+impl StorageType {
+    /// Number of bytes the type occupies in its slot; `string` and `bytes` take the whole slot.
+    pub fn size(&self) -> usize {
+        match self {
+            StorageType::Uint(bits) | StorageType::Int(bits) => bits / 8,
+            StorageType::Address => 20,
+            StorageType::Bool => 1,
+            StorageType::FixedBytes(len) => *len,
+            StorageType::String | StorageType::Bytes => 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum StorageValue {
+    Uint(U256),
+    Int(I256),
+    Address(Address),
+    Bool(bool),
+    FixedBytes(Vec<u8>),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+/// Decodes the variable of type `ty` stored at `slot`, `offset` bytes from the low-order end
+/// of the slot (the `offset` reported by `solc --storage-layout` for packed variables).
+/// `slots` maps proven slots to their values; every slot the value spans must be present.
+pub fn decode_storage(
+    slots: &HashMap<B256, U256>,
+    slot: B256,
+    offset: usize,
+    ty: StorageType,
+) -> Result<StorageValue, StorageDecodeError> {
+    let size = ty.size();
+    let whole_bytes = match ty {
+        StorageType::Uint(bits) | StorageType::Int(bits) => bits % 8 == 0,
+        _ => true,
+    };
+    if size == 0 || size > 32 || !whole_bytes {
+        return Err(StorageDecodeError::InvalidSize(size));
+    }
+    if offset + size > 32 {
+        return Err(StorageDecodeError::InvalidOffset { size, offset });
+    }
+
+    let word = proven(slots, slot)?;
+    let field = if size == 32 {
+        word
+    } else {
+        (word >> (offset * 8)) & ((U256::from(1) << (size * 8)) - U256::from(1))
+    };
+
+    Ok(match ty {
+        StorageType::Uint(_) => StorageValue::Uint(field),
+        StorageType::Int(_) => {
+            let sign_bit = U256::from(1) << (size * 8 - 1);
+            let extended = if size < 32 && field & sign_bit != U256::ZERO {
+                field | !((U256::from(1) << (size * 8)) - U256::from(1))
+            } else {
+                field
+            };
+            StorageValue::Int(I256::from_raw(extended))
+        }
+        StorageType::Address => StorageValue::Address(Address::from_word(B256::from(field))),
+        StorageType::Bool => StorageValue::Bool(field != U256::ZERO),
+        StorageType::FixedBytes(len) => {
+            StorageValue::FixedBytes(field.to_be_bytes::<32>()[32 - len..].to_vec())
+        }
+        StorageType::String => {
+            let bytes = decode_bytes(slots, slot, word)?;
+            StorageValue::String(
+                String::from_utf8(bytes).map_err(|_| StorageDecodeError::InvalidUtf8)?,
+            )
+        }
+        StorageType::Bytes => StorageValue::Bytes(decode_bytes(slots, slot, word)?),
+    })
+}
+
+/// Slots needed to decode a variable of type `ty` at `slot`, given the proven value of `slot`.
+/// Only long `string`/`bytes` values span more than one slot. Their data slots are yielded
+/// lazily, since the length they follow from can be anything the slot holds.
+pub fn required_slots(slot: B256, value: U256, ty: StorageType) -> impl Iterator<Item = B256> {
+    let long = matches!(ty, StorageType::String | StorageType::Bytes) && value.bit(0);
+    iter::once(slot).chain(long.then(|| data_slots(slot, value)).into_iter().flatten())
+}
+
+/// The slots holding a long `string`/`bytes` value whose length word is `word`.
+fn data_slots(slot: B256, word: U256) -> impl Iterator<Item = B256> {
+    let len = (word - U256::from(1)) / U256::from(2);
+    let count = (len + U256::from(31)) / U256::from(32);
+    let data_slot: U256 = keccak256(slot).into();
+    (0u64..)
+        .map(U256::from)
+        .take_while(move |i| *i < count)
+        .map(move |i| B256::from(data_slot.wrapping_add(i)))
+}
+
+/// Short values (< 32 bytes) live in the high-order bytes of the slot with `len * 2` in the
+/// lowest byte; long values store `len * 2 + 1` and their data from `keccak256(slot)` onwards.
+fn decode_bytes(
+    slots: &HashMap<B256, U256>,
+    slot: B256,
+    word: U256,
+) -> Result<Vec<u8>, StorageDecodeError> {
+    if !word.bit(0) {
+        let len = (word.byte(0) / 2) as usize;
+        if len >= 32 {
+            return Err(StorageDecodeError::InvalidLength);
+        }
+        return Ok(word.to_be_bytes::<32>()[..len].to_vec());
+    }
+
+    let len: usize = ((word - U256::from(1)) / U256::from(2))
+        .try_into()
+        .map_err(|_| StorageDecodeError::InvalidLength)?;
+    if len < 32 {
+        return Err(StorageDecodeError::InvalidLength);
+    }
+
+    // grows with the slots that are proven only, whatever length the word claims
+    let mut bytes = vec![];
+    for data_slot in data_slots(slot, word) {
+        bytes.extend_from_slice(&proven(slots, data_slot)?.to_be_bytes::<32>());
+    }
+    bytes.truncate(len);
+    Ok(bytes)
+}
+
+fn proven(slots: &HashMap<B256, U256>, slot: B256) -> Result<U256, StorageDecodeError> {
+    slots
+        .get(&slot)
+        .copied()
+        .ok_or(StorageDecodeError::MissingSlot(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, hex};
+
+    use super::*;
+
+    fn slot(n: u64) -> B256 {
+        B256::from(U256::from(n))
+    }
+
+    #[test]
+    fn test_packed_values() {
+        // struct { address owner; uint64 nonce; bool paused; int8 delta; } packed into slot 0
+        let word = U256::from_be_slice(&hex!(
+            "0000fe01000000000000002a1a642f0e3c3af545e7acbd38b07251b3990914f1"
+        ));
+        let slots = HashMap::from([(slot(0), word)]);
+
+        assert_eq!(
+            decode_storage(&slots, slot(0), 0, StorageType::Address).unwrap(),
+            StorageValue::Address(address!("1a642f0e3c3af545e7acbd38b07251b3990914f1"))
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(0), 20, StorageType::Uint(64)).unwrap(),
+            StorageValue::Uint(U256::from(42))
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(0), 28, StorageType::Bool).unwrap(),
+            StorageValue::Bool(true)
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(0), 29, StorageType::Int(8)).unwrap(),
+            StorageValue::Int(I256::try_from(-2).unwrap())
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(0), 28, StorageType::FixedBytes(2)).unwrap(),
+            StorageValue::FixedBytes(vec![0xfe, 0x01])
+        );
+        assert!(matches!(
+            decode_storage(&slots, slot(0), 30, StorageType::Address),
+            Err(StorageDecodeError::InvalidOffset { .. })
+        ));
+        assert!(matches!(
+            decode_storage(&slots, slot(1), 0, StorageType::Uint(256)),
+            Err(StorageDecodeError::MissingSlot(_))
+        ));
+    }
+
+    #[test]
+    fn test_short_string() {
+        // "hdp" stored inline: data in the high-order bytes, 2 * len in the lowest byte
+        let mut word = [0u8; 32];
+        word[..3].copy_from_slice(b"hdp");
+        word[31] = 6;
+        let slots = HashMap::from([(slot(3), U256::from_be_bytes(word))]);
+
+        assert_eq!(
+            required_slots(slot(3), slots[&slot(3)], StorageType::String).collect::<Vec<_>>(),
+            vec![slot(3)]
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(3), 0, StorageType::String).unwrap(),
+            StorageValue::String("hdp".to_string())
+        );
+    }
+
+    #[test]
+    fn test_long_bytes() {
+        let value: Vec<u8> = (0..40).collect();
+        let data_slot: U256 = keccak256(slot(3)).into();
+
+        let mut slots = HashMap::from([(slot(3), U256::from(40 * 2 + 1))]);
+        assert_eq!(
+            required_slots(slot(3), slots[&slot(3)], StorageType::Bytes).collect::<Vec<_>>(),
+            vec![
+                slot(3),
+                B256::from(data_slot),
+                B256::from(data_slot + U256::from(1))
+            ]
+        );
+
+        slots.insert(B256::from(data_slot), U256::from_be_slice(&value[..32]));
+        assert!(matches!(
+            decode_storage(&slots, slot(3), 0, StorageType::Bytes),
+            Err(StorageDecodeError::MissingSlot(_))
+        ));
+
+        let mut tail = [0u8; 32];
+        tail[..8].copy_from_slice(&value[32..]);
+        slots.insert(
+            B256::from(data_slot + U256::from(1)),
+            U256::from_be_bytes(tail),
+        );
+        assert_eq!(
+            decode_storage(&slots, slot(3), 0, StorageType::Bytes).unwrap(),
+            StorageValue::Bytes(value)
+        );
+    }
+
+    #[test]
+    fn test_unproven_long_length() {
+        // a length of 2^62 bytes with none of its data proven
+        let word = (U256::from(1) << 63) + U256::from(1);
+        let slots = HashMap::from([(slot(3), word)]);
+
+        assert_eq!(
+            required_slots(slot(3), word, StorageType::Bytes)
+                .take(3)
+                .count(),
+            3
+        );
+        assert!(matches!(
+            decode_storage(&slots, slot(3), 0, StorageType::Bytes),
+            Err(StorageDecodeError::MissingSlot(_))
+        ));
+        assert!(matches!(
+            decode_storage(
+                &HashMap::from([(slot(3), U256::MAX)]),
+                slot(3),
+                0,
+                StorageType::Bytes
+            ),
+            Err(StorageDecodeError::InvalidLength)
+        ));
+    }
+}
@@ -0,0 +1,416 @@
+use alloy_primitives::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::{HdpAccount, ProcessedMPTProof},
+    mpt::{verify_storage, MptError},
+    storage::HdpStorage,
+    storage_layout::StoragePath,
+    storage_value::{decode_storage, StorageDecodeError, StorageType, StorageValue},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error(transparent)]
+    Decode(#[from] StorageDecodeError),
+
+    #[error("proof is for {got}, query is for token {expected}")]
+    TokenMismatch { expected: Address, got: Address },
+
+    #[error("proof is for slot {got}, query needs slot {expected}")]
+    SlotMismatch { expected: B256, got: B256 },
+
+    #[error("slot decoded to {got:?}, expected a {expected:?}")]
+    UnexpectedValue {
+        expected: StorageType,
+        got: StorageValue,
+    },
+}
+
+/// Where an ERC-20 token keeps its `balanceOf` mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum Erc20Layout {
+    /// OpenZeppelin `ERC20`: `_balances` at slot 0.
+    OpenZeppelin,
+    /// Solmate `ERC20`: `balanceOf` at slot 3, after `name`, `symbol` and `totalSupply`.
+    Solmate,
+    /// `mapping(address => uint256)` at the given slot.
+    Custom(U256),
+}
+
+impl Erc20Layout {
+    pub fn balances_slot(&self) -> U256 {
+        match self {
+            Erc20Layout::OpenZeppelin => U256::ZERO,
+            Erc20Layout::Solmate => U256::from(3),
+            Erc20Layout::Custom(slot) => *slot,
+        }
+    }
+}
+
+/// Where an ERC-721 token keeps its owner mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub enum Erc721Layout {
+    /// OpenZeppelin `ERC721`: `_owners` at slot 2, after `_name` and `_symbol`.
+    OpenZeppelin,
+    /// Solmate `ERC721`: `_ownerOf` at slot 2, after `name` and `symbol`.
+    Solmate,
+    /// `mapping(uint256 => address)` at the given slot.
+    Custom(U256),
+}
+
+impl Erc721Layout {
+    pub fn owners_slot(&self) -> U256 {
+        match self {
+            Erc721Layout::OpenZeppelin | Erc721Layout::Solmate => U256::from(2),
+            Erc721Layout::Custom(slot) => *slot,
+        }
+    }
+}
+
+/// `balanceOf(holder)` of an ERC-20 token at `block_number`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Erc20Balance {
+    pub token: Address,
+    pub holder: Address,
+    pub block_number: u64,
+    pub layout: Erc20Layout,
+}
+
+pub fn erc20_balance(
+    token: Address,
+    holder: Address,
+    block_number: u64,
+    layout: Erc20Layout,
+) -> Erc20Balance {
+    Erc20Balance {
+        token,
+        holder,
+        block_number,
+        layout,
+    }
+}
+
+impl Erc20Balance {
+    pub fn path(&self) -> StoragePath {
+        StoragePath::new(self.layout.balances_slot()).mapping(self.holder)
+    }
+
+    /// The storage request for the balance slot, carrying its `eth_getProof` storage proof.
+    pub fn storage(&self, proof: Vec<Bytes>) -> HdpStorage {
+        HdpStorage::from_path(
+            self.token,
+            &self.path(),
+            vec![ProcessedMPTProof {
+                block_number: self.block_number,
+                proof,
+            }],
+        )
+    }
+
+    /// Verifies the token's account proof and the balance slot's storage proof against
+    /// `state_root`, and decodes the balance. A holder without a slot has a zero balance.
+    pub fn verify(
+        &self,
+        state_root: B256,
+        account: &HdpAccount,
+        storage: &HdpStorage,
+    ) -> Result<U256, TokenError> {
+        let slot = self.path().slot();
+        match verify_slot(
+            self.token,
+            self.block_number,
+            slot,
+            state_root,
+            account,
+            storage,
+            StorageType::Uint(256),
+        )? {
+            StorageValue::Uint(balance) => Ok(balance),
+            got => Err(TokenError::UnexpectedValue {
+                expected: StorageType::Uint(256),
+                got,
+            }),
+        }
+    }
+}
+
+/// `ownerOf(token_id)` of an ERC-721 token at `block_number`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Erc721Owner {
+    pub token: Address,
+    pub token_id: U256,
+    pub block_number: u64,
+    pub layout: Erc721Layout,
+}
+
+pub fn erc721_owner(
+    token: Address,
+    token_id: U256,
+    block_number: u64,
+    layout: Erc721Layout,
+) -> Erc721Owner {
+    Erc721Owner {
+        token,
+        token_id,
+        block_number,
+        layout,
+    }
+}
+
+impl Erc721Owner {
+    pub fn path(&self) -> StoragePath {
+        StoragePath::new(self.layout.owners_slot()).mapping(self.token_id)
+    }
+
+    /// The storage request for the owner slot, carrying its `eth_getProof` storage proof.
+    pub fn storage(&self, proof: Vec<Bytes>) -> HdpStorage {
+        HdpStorage::from_path(
+            self.token,
+            &self.path(),
+            vec![ProcessedMPTProof {
+                block_number: self.block_number,
+                proof,
+            }],
+        )
+    }
+
+    /// Verifies the token's account proof and the owner slot's storage proof against
+    /// `state_root`, and decodes the owner. Returns `None` for unminted or burned tokens.
+    pub fn verify(
+        &self,
+        state_root: B256,
+        account: &HdpAccount,
+        storage: &HdpStorage,
+    ) -> Result<Option<Address>, TokenError> {
+        let slot = self.path().slot();
+        match verify_slot(
+            self.token,
+            self.block_number,
+            slot,
+            state_root,
+            account,
+            storage,
+            StorageType::Address,
+        )? {
+            StorageValue::Address(owner) if owner.is_zero() => Ok(None),
+            StorageValue::Address(owner) => Ok(Some(owner)),
+            got => Err(TokenError::UnexpectedValue {
+                expected: StorageType::Address,
+                got,
+            }),
+        }
+    }
+}
+
+fn verify_slot(
+    token: Address,
+    block_number: u64,
+    slot: B256,
+    state_root: B256,
+    account: &HdpAccount,
+    storage: &HdpStorage,
+    ty: StorageType,
+) -> Result<StorageValue, TokenError> {
+    if account.address != token {
+        return Err(TokenError::TokenMismatch {
+            expected: token,
+            got: account.address,
+        });
+    }
+    if storage.slot != slot {
+        return Err(TokenError::SlotMismatch {
+            expected: slot,
+            got: storage.slot,
+        });
+    }
+
+    let slots = verify_storage(
+        state_root,
+        block_number,
+        account,
+        std::slice::from_ref(storage),
+    )?;
+    Ok(decode_storage(&slots, slot, 0, ty)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::keccak256;
+    use reth_primitives::{address, b256, hex};
+
+    use super::*;
+
+    // state trie of an OpenZeppelin ERC-20 (0xaa..), a Solmate ERC-20 (0xbb..) and an
+    // OpenZeppelin ERC-721 (0xcc..), with balances and tokens held by `HOLDER`
+    const STATE_ROOT: B256 =
+        b256!("5be1532f16f6db943a5ece30e860d0f284860dc149b83e038ee22927f8fe27f7");
+    const BLOCK: u64 = 20000000;
+    const HOLDER: Address = address!("1a642f0e3c3af545e7acbd38b07251b3990914f1");
+    const OTHER: Address = address!("75cec1db9dceb703200eaa6595f66885c962b920");
+
+    fn account(address: Address, proof: Vec<Bytes>) -> HdpAccount {
+        HdpAccount {
+            address,
+            account_key: hex::encode(keccak256(address)),
+            proofs: vec![ProcessedMPTProof {
+                block_number: BLOCK,
+                proof,
+            }],
+            claims: vec![],
+        }
+    }
+
+    fn oz_erc20_account() -> HdpAccount {
+        account(
+            address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            vec![
+                Bytes::from(hex!("f8718080808080a037bec0bb02df76e35431dfabe09afa94604627d01d95cf18a0eece2c2cbfe4b48080808080808080a0f3021c8331346b3886c5be53627c009edc541c087aba5712373db6796b53ecfda006bc6dc7d79dd58e2f1e3fef4dccac834bad37010feb2be9e62525133d906dc180")),
+                Bytes::from(hex!("f869a03ab9a75647463db7d9263bfdf0f9b455fd5a2ff89f446d3dfa3dfe67cae5649db846f8440180a088eda80db06a791e67291ac81f44ba9d422f942648855d868729820443ffb50ea01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+            ],
+        )
+    }
+
+    fn oz_erc20_holder_proof() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f85180808080a0a0f8e857a81eb1048fa1ec73d51045b9893f623ff08d444f43a7c2e47d884d7f80808080808080808080a08dd2f50e565826123db5646c22bdd8de7d1d63ebdb8050c4bb2f0e2fd7000e4580")),
+        Bytes::from(hex!("eca03815a5423157d784c6e78f2acecef5b9f52d6b0e01754416c1ec4d3eeb9b4bbf8a893635c9adc5dea00000")),
+        ]
+    }
+
+    fn oz_erc20_other_proof() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f85180808080a0a0f8e857a81eb1048fa1ec73d51045b9893f623ff08d444f43a7c2e47d884d7f80808080808080808080a08dd2f50e565826123db5646c22bdd8de7d1d63ebdb8050c4bb2f0e2fd7000e4580")),
+        ]
+    }
+
+    fn solmate_erc20_account() -> HdpAccount {
+        account(
+            address!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            vec![
+                Bytes::from(hex!("f8718080808080a037bec0bb02df76e35431dfabe09afa94604627d01d95cf18a0eece2c2cbfe4b48080808080808080a0f3021c8331346b3886c5be53627c009edc541c087aba5712373db6796b53ecfda006bc6dc7d79dd58e2f1e3fef4dccac834bad37010feb2be9e62525133d906dc180")),
+                Bytes::from(hex!("f869a03b279aac74f4f3342ef71cc0a5321aa36b383c525c7260e761ee23299c5f2f1fb846f8440180a0e27069962ab0459a3b198197801bfd1e56cb80658658bc513068c6f521d52dbfa01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+            ],
+        )
+    }
+
+    fn solmate_erc20_holder_proof() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f851a0b4deb586541fb3059d91c0cad0299cca44c6b8faf8a1d39575e33f5bc62b2cb9808080a0afadc5823a94b9491e6974f6516046e2185db9414f781f3bd7f17d242d10ff82808080808080808080808080")),
+        Bytes::from(hex!("e2a0356f82541746580e6c99aeab1fc46093ca8b50551b260c59464e5c640e50fae22a")),
+        ]
+    }
+
+    fn oz_erc721_account() -> HdpAccount {
+        account(
+            address!("cccccccccccccccccccccccccccccccccccccccc"),
+            vec![
+                Bytes::from(hex!("f8718080808080a037bec0bb02df76e35431dfabe09afa94604627d01d95cf18a0eece2c2cbfe4b48080808080808080a0f3021c8331346b3886c5be53627c009edc541c087aba5712373db6796b53ecfda006bc6dc7d79dd58e2f1e3fef4dccac834bad37010feb2be9e62525133d906dc180")),
+                Bytes::from(hex!("f869a03057b39b049c7df5dfa86c4b0869abe798cef059571a5a1e5bbf5168cf6c097bb846f8440180a0d0b392cc13390f2762ce5353cf5f2d45c2ea297edbee3799478534704461264ca01a578b7a4b0b5755db6d121b4118d4bc68fe170dca840c59bc922f14175a76b0")),
+            ],
+        )
+    }
+
+    fn oz_erc721_token_7_proof() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f851a0a6527e7adbfebe9a29c8a1668081c72729f60069660119a24db925a044984675808080808080a024fee0d7b4b87490239d916bf8db14863911643b17ec04c4ff581fbae0efa5a9808080808080808080")),
+        Bytes::from(hex!("f7a03613dffd80ba7541d4ea05a3202a3f515d93c4b983db32843fbe26e885c3d90095941a642f0e3c3af545e7acbd38b07251b3990914f1")),
+        ]
+    }
+
+    fn oz_erc721_token_8_proof() -> Vec<Bytes> {
+        vec![
+        Bytes::from(hex!("f851a0a6527e7adbfebe9a29c8a1668081c72729f60069660119a24db925a044984675808080808080a024fee0d7b4b87490239d916bf8db14863911643b17ec04c4ff581fbae0efa5a9808080808080808080")),
+        ]
+    }
+
+    #[test]
+    fn test_erc20_openzeppelin() {
+        let token = oz_erc20_account();
+        let query = erc20_balance(token.address, HOLDER, BLOCK, Erc20Layout::OpenZeppelin);
+        let storage = query.storage(oz_erc20_holder_proof());
+
+        assert_eq!(
+            query.verify(STATE_ROOT, &token, &storage).unwrap(),
+            U256::from(1000) * U256::from(10).pow(U256::from(18))
+        );
+
+        // `OTHER` never held the token: its slot is proven absent
+        let other = erc20_balance(token.address, OTHER, BLOCK, Erc20Layout::OpenZeppelin);
+        let storage = other.storage(oz_erc20_other_proof());
+        assert_eq!(
+            other.verify(STATE_ROOT, &token, &storage).unwrap(),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn test_erc20_solmate() {
+        let token = solmate_erc20_account();
+        let query = erc20_balance(token.address, HOLDER, BLOCK, Erc20Layout::Solmate);
+        let storage = query.storage(solmate_erc20_holder_proof());
+        assert_eq!(
+            query.verify(STATE_ROOT, &token, &storage).unwrap(),
+            U256::from(42)
+        );
+
+        // the same slot through an explicit layout
+        let custom = erc20_balance(
+            token.address,
+            HOLDER,
+            BLOCK,
+            Erc20Layout::Custom(U256::from(3)),
+        );
+        assert_eq!(custom.path().slot(), query.path().slot());
+
+        // the OpenZeppelin slot is not the one that was proven
+        let wrong_layout = erc20_balance(token.address, HOLDER, BLOCK, Erc20Layout::OpenZeppelin);
+        assert!(matches!(
+            wrong_layout.verify(STATE_ROOT, &token, &storage),
+            Err(TokenError::SlotMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_erc721_owner() {
+        let token = oz_erc721_account();
+        let minted = erc721_owner(
+            token.address,
+            U256::from(7),
+            BLOCK,
+            Erc721Layout::OpenZeppelin,
+        );
+        let storage = minted.storage(oz_erc721_token_7_proof());
+        assert_eq!(
+            minted.verify(STATE_ROOT, &token, &storage).unwrap(),
+            Some(HOLDER)
+        );
+
+        let unminted = erc721_owner(
+            token.address,
+            U256::from(8),
+            BLOCK,
+            Erc721Layout::OpenZeppelin,
+        );
+        let storage = unminted.storage(oz_erc721_token_8_proof());
+        assert_eq!(unminted.verify(STATE_ROOT, &token, &storage).unwrap(), None);
+    }
+
+    #[test]
+    fn test_token_mismatch() {
+        let query = erc20_balance(
+            address!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            HOLDER,
+            BLOCK,
+            Erc20Layout::OpenZeppelin,
+        );
+        let storage = query.storage(oz_erc20_holder_proof());
+        assert!(matches!(
+            query.verify(STATE_ROOT, &oz_erc20_account(), &storage),
+            Err(TokenError::TokenMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use alloy_consensus::{Transaction as _, TxEnvelope};
+use alloy_eips::{
+    eip2718::{Decodable2718, Eip2718Error},
+    eip2930::AccessList,
+    eip7702::SignedAuthorization,
+};
+use alloy_primitives::{Address, Bytes, SignatureError, B256, U256};
+use reth_primitives::Header;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::ProcessedMPTProof,
+    mpt::{verify_inclusion, MptError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError {
+    #[error("proof is for block {proof}, header is block {header}")]
+    BlockNumberMismatch { proof: u64, header: u64 },
+
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error("invalid transaction encoding: {0}")]
+    Decode(#[from] Eip2718Error),
+
+    #[error("sender recovery failed: {0}")]
+    Signature(#[from] SignatureError),
+}
+
+/// Inclusion proof of the transaction at `tx_index` in the block's `transactions_root`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct HdpTransaction {
+    pub tx_index: u64,
+    pub proof: ProcessedMPTProof,
+}
+
+/// A decoded legacy, EIP-2930, EIP-1559, EIP-4844 or EIP-7702 transaction.
+/// Fields that do not exist for a given type are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub hash: B256,
+    pub tx_type: u8,
+    pub sender: Address,
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub gas_limit: u128,
+    pub gas_price: Option<u128>,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub max_fee_per_blob_gas: Option<u128>,
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: Option<AccessList>,
+    pub blob_versioned_hashes: Option<Vec<B256>>,
+    pub authorization_list: Option<Vec<SignedAuthorization>>,
+}
+
+/// Decodes an EIP-2718 encoded transaction, as stored in the transactions trie,
+/// and recovers its sender.
+pub fn decode_transaction(encoded: &[u8]) -> Result<Transaction, TransactionError> {
+    let envelope = TxEnvelope::decode_2718(&mut &encoded[..])?;
+    let sender = envelope.recover_signer()?;
+
+    Ok(Transaction {
+        hash: *envelope.tx_hash(),
+        tx_type: envelope.ty(),
+        sender,
+        chain_id: envelope.chain_id(),
+        nonce: envelope.nonce(),
+        gas_limit: envelope.gas_limit(),
+        gas_price: envelope.gas_price(),
+        max_fee_per_gas: envelope.max_fee_per_gas(),
+        max_priority_fee_per_gas: envelope.max_priority_fee_per_gas(),
+        max_fee_per_blob_gas: envelope.max_fee_per_blob_gas(),
+        to: envelope.to().to().copied(),
+        value: envelope.value(),
+        input: Bytes::copy_from_slice(envelope.input()),
+        access_list: envelope.access_list().cloned(),
+        blob_versioned_hashes: envelope.blob_versioned_hashes().map(<[B256]>::to_vec),
+        authorization_list: envelope.authorization_list().map(<[_]>::to_vec),
+    })
+}
+
+/// Verifies the transaction against the `transactions_root` of a verified header and decodes it.
+pub fn verify_transaction(
+    transaction: &HdpTransaction,
+    header: &Header,
+) -> Result<Transaction, TransactionError> {
+    if transaction.proof.block_number != header.number {
+        return Err(TransactionError::BlockNumberMismatch {
+            proof: transaction.proof.block_number,
+            header: header.number,
+        });
+    }
+
+    let key = alloy_rlp::encode(transaction.tx_index);
+    let encoded = verify_inclusion(header.transactions_root, &key, &transaction.proof.proof)?;
+    decode_transaction(&encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, b256, hex};
+
+    use super::*;
+
+    const SENDER: Address = address!("1a642f0e3c3af545e7acbd38b07251b3990914f1");
+    const TO: Address = address!("75cec1db9dceb703200eaa6595f66885c962b920");
+
+    #[test]
+    fn test_decode_legacy() {
+        let tx = decode_transaction(&hex!("f86f078504a817c8008252089475cec1db9dceb703200eaa6595f66885c962b92087038d7ea4c68000808401546d71a0530a4289674290df62522935006a37ca044afe5571613327b730adef0c0ef61da0675b0defbdfbbc83632ef59914d1394b75b4dc00eb5ca6b5cab60e223d75b007")).unwrap();
+
+        assert_eq!(tx.tx_type, 0);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.chain_id, Some(11155111));
+        assert_eq!(tx.nonce, 7);
+        assert_eq!(tx.gas_price, Some(20_000_000_000));
+        assert_eq!(tx.to, Some(TO));
+        assert_eq!(tx.value, U256::from(1_000_000_000_000_000_u64));
+        assert_eq!(tx.access_list, None);
+        assert_eq!(
+            tx.hash,
+            b256!("59d0632d66cd95f7d339e46b730bd1b6adb4856539a64c09681b19516a5b3538")
+        );
+    }
+
+    #[test]
+    fn test_decode_eip1559_create() {
+        let tx = decode_transaction(&hex!("02f86083aa36a709843b9aca008506fc23ac00830186a08080856080604052c001a0fb6197940230afd988b50bb71fe4cbb703acc3bd63513b1a32dc1c98159961eca02a26de84a375bc959d24a0e30f6262ab22c40f363813d93ef435a23623786fc6")).unwrap();
+
+        assert_eq!(tx.tx_type, 2);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.to, None);
+        assert_eq!(tx.max_fee_per_gas, 30_000_000_000);
+        assert_eq!(tx.max_priority_fee_per_gas, Some(1_000_000_000));
+        assert_eq!(tx.input, Bytes::from(hex!("6080604052")));
+        assert_eq!(tx.access_list, Some(AccessList::default()));
+    }
+
+    #[test]
+    fn test_decode_eip4844() {
+        let tx = decode_transaction(&hex!("03f89583aa36a70a843b9aca008506fc23ac008252089475cec1db9dceb703200eaa6595f66885c962b9208080c0843b9aca00e1a001a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d801a022fd85100764e0aecf31c3e83e54fa96698f5c11615b1a9d12fe5a43809a0e2da048965cb4bb2e290bfd8adf2e866dc9adc058759028c69845dc76256201eced54")).unwrap();
+
+        assert_eq!(tx.tx_type, 3);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.max_fee_per_blob_gas, Some(1_000_000_000));
+        assert_eq!(
+            tx.blob_versioned_hashes,
+            Some(vec![b256!(
+                "01a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+            )])
+        );
+    }
+
+    #[test]
+    fn test_decode_eip7702() {
+        let tx = decode_transaction(&hex!("04f8cf83aa36a70b843b9aca008506fc23ac0082ea609475cec1db9dceb703200eaa6595f66885c962b9208080c0f85ff85d83aa36a79475cec1db9dceb703200eaa6595f66885c962b9200b80a0d55f0cebd59949057b6930af04255ff7e7f8b67e4933b582a4472fc2adf4a578a005e94c26f058f081a94ff82136c7c2f5fd3e54bb5f7d711fb0c2ae2a0519426e01a0b11821e2404544388dac427735ccc6d4b7dee11ca10248dc44722df9bc329242a04d9f418b77601be6a8a6a9fba816566946ca12c0abba4c21a1318cdbde201420")).unwrap();
+
+        assert_eq!(tx.tx_type, 4);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.nonce, 11);
+        let authorizations = tx.authorization_list.unwrap();
+        assert_eq!(authorizations.len(), 1);
+        assert_eq!(authorizations[0].address, TO);
+    }
+
+    #[test]
+    fn test_verify_eip2930_transaction() {
+        // transactions trie of five transactions, proving the one at index 1
+        let transaction = HdpTransaction {
+            tx_index: 1,
+            proof: ProcessedMPTProof {
+                block_number: 0,
+                proof: vec![
+                    Bytes::from(hex!("f851a0429a5185c370061a02a3b9fd7713bda3e7bd16ba92c1bd4c837d13568734defe80808080808080a056eafeb62bd6199c6160d9263f4b3559fa85d395013e0ba5aa439cc0b34b462a8080808080808080")),
+                    Bytes::from(hex!("f89180a0c83791dc8440872d14da132b84fd990af62ed23836194f8f539c03c2f9f00292a0b9f47f0246686ceba19a0f1da75856df2e733d8b9af9b01cadf20c548e5753f4a061cf82898f6e19c43e2c08abde2ffd97d8933cbc43870b8e36aa5b97ab081092a07210cfb56b778f397322fe0ffe0c72925736363341eaabd1db32804dae61b8d1808080808080808080808080")),
+                    Bytes::from(hex!("f8ac20b8a901f8a683aa36a7088504a817c80082c3509475cec1db9dceb703200eaa6595f66885c962b9208084a9059cbbf838f79475cec1db9dceb703200eaa6595f66885c962b920e1a0000000000000000000000000000000000000000000000000000000000000000101a08d15ce974266ea7b9c0b4b50c34f14c2a2e5c73e1aa2beba3bbe2930364e8597a00310af03da10baa97c90e84be537a3e84856f26ffce78da9e1bebb718871b890")),
+                ],
+            },
+        };
+        let header = Header {
+            transactions_root: b256!(
+                "b4f6b2908f9551fbbfadb965f621792b7a17424a3c3bc3d0fbae5a50e860d672"
+            ),
+            ..Default::default()
+        };
+
+        let tx = verify_transaction(&transaction, &header).unwrap();
+        assert_eq!(tx.tx_type, 1);
+        assert_eq!(tx.sender, SENDER);
+        assert_eq!(tx.nonce, 8);
+        assert_eq!(tx.input, Bytes::from(hex!("a9059cbb")));
+        assert_eq!(tx.access_list.unwrap().0[0].address, TO);
+
+        let wrong_index = HdpTransaction {
+            tx_index: 2,
+            ..transaction.clone()
+        };
+        assert!(verify_transaction(&wrong_index, &header).is_err());
+
+        let other_block = Header {
+            number: 1,
+            ..header
+        };
+        assert!(matches!(
+            verify_transaction(&transaction, &other_block),
+            Err(TransactionError::BlockNumberMismatch {
+                proof: 0,
+                header: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{hex, keccak256, Address, B256};
+use alloy_sol_types::{sol, SolValue};
+use reth_primitives::Header;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "legacy-mpt")]
+use crate::mpt::verify_account_proof;
+use crate::{
+    diff::{diff_account_in, AccountDiff, Change, DiffError, SlotDiff},
+    guest_mmr::{self, verify_header, MmrError, HASH_ID},
+    hash::HashId,
+    json::MmrJson,
+    mpt_cached::CachedVerifier,
+    outcome::Outcome,
+    rlp::decode_header,
+};
+
+/// Input of the guest, with the MMR types of the hasher it is built with.
+pub type GuestInput = MmrJson<guest_mmr::MmrMeta, guest_mmr::Header>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PublicValuesError {
+    #[error(transparent)]
+    Abi(#[from] alloy_sol_types::Error),
+
+    #[error("unknown hash id {0}")]
+    UnknownHashId(u8),
+
+    #[error("unknown outcome {0}")]
+    UnknownOutcome(u8),
+}
+
+/// Everything the guest commits: the MMR hasher it checked headers with, the MMR root and
+/// size the input claims, the outcome, and if it is `Valid` the proven headers and diffs.
+/// A consumer has to trust the MMR for any of it to mean something about the chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct PublicValues {
+    pub hash_id: HashId,
+    pub mmr_root: B256,
+    pub mmr_size: u128,
+    pub outcome: Outcome,
+    pub headers: Vec<ProvenHeader>,
+    pub diffs: Vec<AccountDiff>,
+}
+
+/// A header verified against the MMR.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct ProvenHeader {
+    pub number: u64,
+    pub hash: B256,
+}
+
+sol! {
+    /// ABI layout of [`PublicValues`], committed by the guest so contracts can decode it.
+    /// `outcome`, `index` and `blockNumber` are [`Outcome::to_parts`].
+    struct PublicValuesStruct {
+        uint8 hashId;
+        bytes32 mmrRoot;
+        uint128 mmrSize;
+        uint8 outcome;
+        uint64 index;
+        uint64 blockNumber;
+        ProvenHeaderStruct[] headers;
+        AccountDiffStruct[] diffs;
+    }
+
+    struct ProvenHeaderStruct {
+        uint64 number;
+        bytes32 hash;
+    }
+
+    struct AccountDiffStruct {
+        address account;
+        uint64 fromBlock;
+        uint64 toBlock;
+        bool existedBefore;
+        bool existsAfter;
+        uint64 nonceBefore;
+        uint64 nonceAfter;
+        uint256 balanceBefore;
+        uint256 balanceAfter;
+        bytes32 storageRootBefore;
+        bytes32 storageRootAfter;
+        bytes32 codeHashBefore;
+        bytes32 codeHashAfter;
+        SlotDiffStruct[] slots;
+    }
+
+    struct SlotDiffStruct {
+        bytes32 slot;
+        uint256 valueBefore;
+        uint256 valueAfter;
+    }
+}
+
+impl PublicValues {
+    /// The bytes the guest commits.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let (outcome, index, block_number) = self.outcome.to_parts();
+        PublicValuesStruct {
+            hashId: self.hash_id as u8,
+            mmrRoot: self.mmr_root,
+            mmrSize: self.mmr_size,
+            outcome,
+            index,
+            blockNumber: block_number,
+            headers: self
+                .headers
+                .iter()
+                .map(|header| ProvenHeaderStruct {
+                    number: header.number,
+                    hash: header.hash,
+                })
+                .collect(),
+            diffs: self.diffs.iter().map(Into::into).collect(),
+        }
+        .abi_encode()
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, PublicValuesError> {
+        let values = PublicValuesStruct::abi_decode(data, true)?;
+        let hash_id = HashId::try_from(values.hashId).map_err(PublicValuesError::UnknownHashId)?;
+        let outcome =
+            Outcome::from_parts(values.outcome, values.index, values.blockNumber, hash_id)
+                .ok_or(PublicValuesError::UnknownOutcome(values.outcome))?;
+        Ok(Self {
+            hash_id,
+            mmr_root: values.mmrRoot,
+            mmr_size: values.mmrSize,
+            outcome,
+            headers: values
+                .headers
+                .into_iter()
+                .map(|header| ProvenHeader {
+                    number: header.number,
+                    hash: header.hash,
+                })
+                .collect(),
+            diffs: values.diffs.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl From<&AccountDiff> for AccountDiffStruct {
+    fn from(diff: &AccountDiff) -> Self {
+        Self {
+            account: diff.address,
+            fromBlock: diff.from_block,
+            toBlock: diff.to_block,
+            existedBefore: diff.exists.old,
+            existsAfter: diff.exists.new,
+            nonceBefore: diff.nonce.old,
+            nonceAfter: diff.nonce.new,
+            balanceBefore: diff.balance.old,
+            balanceAfter: diff.balance.new,
+            storageRootBefore: diff.storage_root.old,
+            storageRootAfter: diff.storage_root.new,
+            codeHashBefore: diff.code_hash.old,
+            codeHashAfter: diff.code_hash.new,
+            slots: diff
+                .storage
+                .iter()
+                .map(|slot| SlotDiffStruct {
+                    slot: slot.slot,
+                    valueBefore: slot.value.old,
+                    valueAfter: slot.value.new,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<AccountDiffStruct> for AccountDiff {
+    fn from(diff: AccountDiffStruct) -> Self {
+        Self {
+            address: diff.account,
+            from_block: diff.fromBlock,
+            to_block: diff.toBlock,
+            exists: Change {
+                old: diff.existedBefore,
+                new: diff.existsAfter,
+            },
+            nonce: Change {
+                old: diff.nonceBefore,
+                new: diff.nonceAfter,
+            },
+            balance: Change {
+                old: diff.balanceBefore,
+                new: diff.balanceAfter,
+            },
+            storage_root: Change {
+                old: diff.storageRootBefore,
+                new: diff.storageRootAfter,
+            },
+            code_hash: Change {
+                old: diff.codeHashBefore,
+                new: diff.codeHashAfter,
+            },
+            storage: diff
+                .slots
+                .into_iter()
+                .map(|slot| SlotDiff {
+                    slot: slot.slot,
+                    value: Change {
+                        old: slot.valueBefore,
+                        new: slot.valueAfter,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Only printed inside the zkVM, where the executor turns them into cycle counts. The
+/// `report` markers also add them to the execution report's `cycle_tracker`.
+macro_rules! cycle_tracker {
+    ($event:literal, $name:literal) => {
+        #[cfg(target_os = "zkvm")]
+        println!(concat!("cycle-tracker-report-", $event, ": ", $name));
+    };
+}
+
+/// Verifies headers against the MMR, then every account and storage proof against the
+/// state of its block, then proves the diffs. This is all the guest program does.
+pub fn verify_all(input: &GuestInput) -> PublicValues {
+    let (outcome, headers, diffs) = match verify(input) {
+        Ok((headers, diffs)) => (Outcome::Valid, headers, diffs),
+        Err(outcome) => (outcome, vec![], vec![]),
+    };
+    PublicValues {
+        hash_id: HASH_ID,
+        mmr_root: input.meta.root_word(),
+        mmr_size: input.meta.size,
+        outcome,
+        headers,
+        diffs,
+    }
+}
+
+fn verify(input: &GuestInput) -> Result<(Vec<ProvenHeader>, Vec<AccountDiff>), Outcome> {
+    // the mmr hasher is fixed at build time by the `keccak-mmr` / `poseidon-mmr` feature
+    if input.hash_id != HASH_ID {
+        return Err(Outcome::HashIdMismatch {
+            expected: HASH_ID,
+            got: input.hash_id,
+        });
+    }
+
+    // verify all the given headers are valid against the given mmr
+    cycle_tracker!("start", "mmr");
+    let included = input
+        .headers
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, header)| match verify_header(&input.meta, header) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Outcome::HeaderNotInMmr { index }),
+            Err(err) => match err.downcast_ref::<MmrError>() {
+                Some(MmrError::RootMismatch) => Err(Outcome::MmrRootMismatch),
+                // the keccak mmr hashes the decoded rlp, so bad hex fails here, not below
+                None if err.is::<hex::FromHexError>() => Err(Outcome::HeaderDecodeError { index }),
+                _ => Err(Outcome::BadProofShape { index }),
+            },
+        });
+    cycle_tracker!("end", "mmr");
+    included?;
+
+    cycle_tracker!("start", "rlp");
+    let headers = input
+        .headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            header
+                .rlp_bytes()
+                .ok()
+                .and_then(|rlp| Some((decode_header(&rlp).ok()?, keccak256(&rlp))))
+                .ok_or(Outcome::HeaderDecodeError { index })
+        })
+        .collect::<Result<Vec<_>, _>>();
+    cycle_tracker!("end", "rlp");
+    let (headers, hashes): (Vec<_>, Vec<_>) = headers?.into_iter().unzip();
+
+    cycle_tracker!("start", "account mpt");
+    let storage_tries = storage_tries(input, &headers);
+    cycle_tracker!("end", "account mpt");
+    let mut storage_tries = storage_tries?;
+
+    cycle_tracker!("start", "storage mpt");
+    let slots = input
+        .storages
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, storage)| {
+            storage.proofs.iter().try_for_each(|proof| {
+                let block_number = proof.block_number;
+                storage_tries
+                    .get_mut(&(storage.address, block_number))
+                    .ok_or(Outcome::BlockNumberMismatch { block_number })?
+                    .storage(storage.slot, &proof.proof)
+                    .map(drop)
+                    .map_err(|_| Outcome::StorageProofFailure {
+                        index,
+                        block_number,
+                    })
+            })
+        });
+    cycle_tracker!("end", "storage mpt");
+    slots?;
+
+    // diffs are only proven between headers that passed the mmr check
+    cycle_tracker!("start", "diff");
+    let diffs = input
+        .diffs
+        .iter()
+        .enumerate()
+        .map(|(index, diff)| {
+            diff_account_in(&headers, diff).map_err(|err| match err {
+                DiffError::MissingHeader(block_number)
+                | DiffError::BlockNumberMismatch {
+                    got: block_number, ..
+                } => Outcome::BlockNumberMismatch { block_number },
+                DiffError::Mpt(_) => Outcome::DiffProofFailure { index },
+            })
+        })
+        .collect::<Result<Vec<_>, _>>();
+    cycle_tracker!("end", "diff");
+
+    let proven = headers
+        .iter()
+        .zip(hashes)
+        .map(|(header, hash)| ProvenHeader {
+            number: header.number,
+            hash,
+        })
+        .collect();
+    Ok((proven, diffs?))
+}
+
+/// Verifier of the storage trie of every proven account at every block it has a proof for.
+/// Account proofs against the same state root share one verifier, and so do the storage
+/// proofs of an account at a block, so nodes common to several proofs are hashed once.
+fn storage_tries<'a>(
+    input: &'a GuestInput,
+    headers: &[Header],
+) -> Result<HashMap<(Address, u64), CachedVerifier<'a>>, Outcome> {
+    #[cfg(not(feature = "legacy-mpt"))]
+    let mut state_tries: HashMap<B256, CachedVerifier> = HashMap::new();
+    let mut storage_tries = HashMap::new();
+    for (index, account) in input.accounts.iter().enumerate() {
+        for proof in &account.proofs {
+            let block_number = proof.block_number;
+            let state_root = headers
+                .iter()
+                .find(|header| header.number == block_number)
+                .map(|header| header.state_root)
+                .ok_or(Outcome::BlockNumberMismatch { block_number })?;
+            #[cfg(feature = "legacy-mpt")]
+            let proven = verify_account_proof(account.address, state_root, &proof.proof);
+            #[cfg(not(feature = "legacy-mpt"))]
+            let proven = state_tries
+                .entry(state_root)
+                .or_insert_with(|| CachedVerifier::new(state_root))
+                .account(account.address, &proof.proof);
+            match proven {
+                Ok(Some(proven)) => {
+                    storage_tries.insert(
+                        (account.address, block_number),
+                        CachedVerifier::new(proven.storage_root),
+                    );
+                }
+                _ => {
+                    return Err(Outcome::AccountProofFailure {
+                        index,
+                        block_number,
+                    })
+                }
+            }
+        }
+    }
+    Ok(storage_tries)
+}
+
+// the fixture is a keccak mmr
+#[cfg(all(test, not(feature = "poseidon-mmr")))]
+mod tests {
+    use alloy_primitives::{b256, U256};
+
+    use super::*;
+    use crate::diff::HdpDiff;
+
+    fn fixture() -> GuestInput {
+        serde_json::from_str(include_str!("../../script/keccak-test.json")).unwrap()
+    }
+
+    #[test]
+    fn test_verify_fixture() {
+        let input = fixture();
+        assert_eq!(
+            verify_all(&input),
+            PublicValues {
+                hash_id: HashId::Keccak,
+                mmr_root: input.meta.root,
+                mmr_size: 13024091,
+                outcome: Outcome::Valid,
+                headers: vec![ProvenHeader {
+                    number: 5641516,
+                    hash: b256!("75a0075713d3a55e62abf8479a178b1e230ca0eab1a97b30bdefd195ea0a22b8"),
+                }],
+                diffs: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_diffs_are_between_committed_headers() {
+        let mut input = fixture();
+        let block_number = input.accounts[0].proofs[0].block_number;
+        input.diffs = vec![HdpDiff {
+            from_block: block_number,
+            to_block: block_number,
+            account: input.accounts[0].clone(),
+            storages: input.storages.clone(),
+        }];
+
+        let public_values = verify_all(&input);
+        assert_eq!(public_values.outcome, Outcome::Valid);
+        assert!(public_values.diffs[0].is_unchanged());
+        for diff in &public_values.diffs {
+            for block in [diff.from_block, diff.to_block] {
+                assert!(public_values
+                    .headers
+                    .iter()
+                    .any(|header| header.number == block));
+            }
+        }
+
+        input.diffs[0].to_block += 1;
+        assert_eq!(
+            verify_all(&input).outcome,
+            Outcome::BlockNumberMismatch {
+                block_number: block_number + 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_failures() {
+        let outcome = |edit: fn(&mut GuestInput)| {
+            let mut input = fixture();
+            edit(&mut input);
+            let public_values = verify_all(&input);
+            assert!(public_values.headers.is_empty());
+            assert!(public_values.diffs.is_empty());
+            assert_eq!(public_values.mmr_size, input.meta.size);
+            public_values.outcome
+        };
+        let block_number = fixture().accounts[0].proofs[0].block_number;
+
+        assert_eq!(
+            outcome(|input| input.hash_id = HashId::Poseidon),
+            Outcome::HashIdMismatch {
+                expected: HashId::Keccak,
+                got: HashId::Poseidon,
+            }
+        );
+        assert_eq!(
+            outcome(|input| input.meta.root = B256::ZERO),
+            Outcome::MmrRootMismatch
+        );
+        assert_eq!(
+            outcome(|input| input.headers[0].proof.leaf_idx = 0),
+            Outcome::BadProofShape { index: 0 }
+        );
+        assert_eq!(
+            outcome(|input| {
+                input.headers[0].proof.mmr_path.pop();
+            }),
+            Outcome::HeaderNotInMmr { index: 0 }
+        );
+        assert_eq!(
+            outcome(|input| input.headers[0].rlp.push('z')),
+            Outcome::HeaderDecodeError { index: 0 }
+        );
+        assert_eq!(
+            outcome(|input| input.accounts[0].address = Address::ZERO),
+            Outcome::AccountProofFailure {
+                index: 0,
+                block_number,
+            }
+        );
+        assert_eq!(
+            outcome(|input| input.storages[0].slot = B256::from(U256::from(2))),
+            Outcome::StorageProofFailure {
+                index: 0,
+                block_number,
+            }
+        );
+        assert_eq!(
+            outcome(|input| input.accounts[0].proofs[0].block_number += 1),
+            Outcome::BlockNumberMismatch {
+                block_number: block_number + 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_abi_roundtrip() {
+        let diff = AccountDiff {
+            address: Address::with_last_byte(1),
+            from_block: 100,
+            to_block: 200,
+            exists: Change {
+                old: false,
+                new: true,
+            },
+            nonce: Change { old: 0, new: 1 },
+            balance: Change {
+                old: U256::ZERO,
+                new: U256::MAX,
+            },
+            storage_root: Change {
+                old: B256::ZERO,
+                new: B256::with_last_byte(2),
+            },
+            code_hash: Change {
+                old: B256::ZERO,
+                new: B256::with_last_byte(3),
+            },
+            storage: vec![SlotDiff {
+                slot: B256::with_last_byte(4),
+                value: Change {
+                    old: U256::from(5),
+                    new: U256::from(6),
+                },
+            }],
+        };
+        let public_values = PublicValues {
+            hash_id: HashId::Poseidon,
+            mmr_root: B256::with_last_byte(7),
+            mmr_size: u128::MAX,
+            outcome: Outcome::Valid,
+            headers: vec![
+                ProvenHeader {
+                    number: 100,
+                    hash: B256::with_last_byte(8),
+                },
+                ProvenHeader {
+                    number: 200,
+                    hash: B256::with_last_byte(9),
+                },
+            ],
+            diffs: vec![diff],
+        };
+        let encoded = public_values.abi_encode();
+        assert_eq!(PublicValues::abi_decode(&encoded).unwrap(), public_values);
+
+        for outcome in [
+            Outcome::HashIdMismatch {
+                expected: HashId::Poseidon,
+                got: HashId::Keccak,
+            },
+            Outcome::MmrRootMismatch,
+            Outcome::BadProofShape { index: 1 },
+            Outcome::HeaderNotInMmr { index: 2 },
+            Outcome::HeaderDecodeError { index: 3 },
+            Outcome::AccountProofFailure {
+                index: 4,
+                block_number: 5,
+            },
+            Outcome::StorageProofFailure {
+                index: 6,
+                block_number: 7,
+            },
+            Outcome::BlockNumberMismatch { block_number: 8 },
+            Outcome::DiffProofFailure { index: 9 },
+        ] {
+            let public_values = PublicValues {
+                hash_id: HashId::Poseidon,
+                mmr_root: B256::ZERO,
+                mmr_size: 0,
+                outcome,
+                headers: vec![],
+                diffs: vec![],
+            };
+            let decoded = PublicValues::abi_decode(&public_values.abi_encode()).unwrap();
+            assert_eq!(decoded, public_values);
+        }
+
+        let mut encoded = encoded;
+        // after the offset of the struct, the hash id, the mmr root and the mmr size
+        encoded[159] = 10;
+        assert!(matches!(
+            PublicValues::abi_decode(&encoded),
+            Err(PublicValuesError::UnknownOutcome(10))
+        ));
+    }
+}
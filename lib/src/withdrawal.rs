@@ -0,0 +1,109 @@
+use alloy_eips::eip4895::Withdrawal;
+use alloy_rlp::Decodable;
+use reth_primitives::Header;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::ProcessedMPTProof,
+    mpt::{verify_inclusion, MptError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum WithdrawalError {
+    #[error("block {0} has no withdrawals root")]
+    MissingWithdrawalsRoot(u64),
+
+    #[error("proof is for block {proof}, header is block {header}")]
+    BlockNumberMismatch { proof: u64, header: u64 },
+
+    #[error(transparent)]
+    Mpt(#[from] MptError),
+
+    #[error("invalid withdrawal encoding: {0}")]
+    Decode(#[from] alloy_rlp::Error),
+}
+
+/// Inclusion proof of the withdrawal at position `index` of a block's withdrawals list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct HdpWithdrawal {
+    pub index: u64,
+    pub proof: ProcessedMPTProof,
+}
+
+/// Verifies the withdrawal against the `withdrawals_root` of a verified post-Shanghai header
+/// and decodes it.
+pub fn verify_withdrawal(
+    withdrawal: &HdpWithdrawal,
+    header: &Header,
+) -> Result<Withdrawal, WithdrawalError> {
+    if withdrawal.proof.block_number != header.number {
+        return Err(WithdrawalError::BlockNumberMismatch {
+            proof: withdrawal.proof.block_number,
+            header: header.number,
+        });
+    }
+    let withdrawals_root = header
+        .withdrawals_root
+        .ok_or(WithdrawalError::MissingWithdrawalsRoot(header.number))?;
+
+    let key = alloy_rlp::encode(withdrawal.index);
+    let encoded = verify_inclusion(withdrawals_root, &key, &withdrawal.proof.proof)?;
+    Ok(Withdrawal::decode(&mut encoded.as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use reth_primitives::{address, b256, hex, Bytes};
+
+    use super::*;
+
+    fn withdrawal() -> HdpWithdrawal {
+        // withdrawals trie of three withdrawals, proving the one at position 2
+        HdpWithdrawal {
+            index: 2,
+            proof: ProcessedMPTProof {
+                block_number: 20000000,
+                proof: vec![
+                    Bytes::from(hex!("f851a0fcb4f9172a065123bafbdbfe6ed5391ce2b8785880a7227371615a2a6c68a40080808080808080a014e80c2975e5a27fc524c18a42e5e34607ef705fe460860aa10ff5a6a30b94618080808080808080")),
+                    Bytes::from(hex!("f85180a05e135a16ac911adfc7b4c79cbf9f4223b376cba89ae95e5e20fe607d56b09698a090c9af6b782063d91d78a3c585b80c8969de99ca5156a5c187868dccbfae15b08080808080808080808080808080")),
+                    Bytes::from(hex!("e620a4e38402625a028203eb941a642f0e3c3af545e7acbd38b07251b3990914f1850773594000")),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify_withdrawal() {
+        let header = Header {
+            number: 20000000,
+            withdrawals_root: Some(b256!(
+                "fec1d733042f28e34a866c384961565e405db56c3430d375ad2ceb6268c64d5b"
+            )),
+            ..Default::default()
+        };
+
+        let proven = verify_withdrawal(&withdrawal(), &header).unwrap();
+        assert_eq!(
+            proven,
+            Withdrawal {
+                index: 40_000_002,
+                validator_index: 1_003,
+                address: address!("1a642f0e3c3af545e7acbd38b07251b3990914f1"),
+                amount: 32_000_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pre_shanghai_header() {
+        let header = Header {
+            number: 20000000,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            verify_withdrawal(&withdrawal(), &header),
+            Err(WithdrawalError::MissingWithdrawalsRoot(20000000))
+        ));
+    }
+}
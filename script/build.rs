@@ -1,5 +1,21 @@
-use sp1_helper::build_program_with_args;
+use sp1_helper::{build_program_with_args, BuildArgs};
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    // one ELF per MMR hasher, so either kind of input can be run without rebuilding
+    for feature in ["keccak-mmr", "poseidon-mmr"] {
+        let mut features = vec![feature.to_string()];
+        // `--features legacy-mpt` on the script builds the guest's previous mpt path
+        if std::env::var_os("CARGO_FEATURE_LEGACY_MPT").is_some() {
+            features.push("legacy-mpt".to_string());
+        }
+        build_program_with_args(
+            "../program",
+            BuildArgs {
+                features,
+                no_default_features: true,
+                elf_name: format!("{feature}-elf"),
+                ..Default::default()
+            },
+        )
+    }
 }
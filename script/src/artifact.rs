@@ -0,0 +1,176 @@
+//! Proofs saved to disk with their verifying key and decoded public values, so they can be
+//! archived and checked again later, whatever the program has become since.
+
+use std::{fs, io, path::Path};
+
+use hdp_lib::verify::{PublicValues, PublicValuesError};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    #[error("{0}: {1}")]
+    Io(String, io::Error),
+
+    #[error("{0}: not a proof artifact: {1}")]
+    Encoding(String, bincode::Error),
+
+    #[error(transparent)]
+    PublicValues(#[from] PublicValuesError),
+
+    #[error("recorded public values do not match the ones committed by the proof")]
+    PublicValuesMismatch,
+
+    #[error("recorded vkey hash {recorded} is not the hash {actual} of the saved vkey")]
+    VkeyMismatch { recorded: String, actual: String },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProofArtifact {
+    /// Name of the input the proof was generated from.
+    pub input: String,
+    /// `bytes32` hash of `vk`.
+    pub vkey_hash: String,
+    /// Verifying key of the program the proof was generated with, which may no longer be
+    /// the one built.
+    pub vk: SP1VerifyingKey,
+    pub public_values: PublicValues,
+    pub proof: SP1ProofWithPublicValues,
+}
+
+impl ProofArtifact {
+    pub fn new(
+        input: String,
+        vk: &SP1VerifyingKey,
+        proof: SP1ProofWithPublicValues,
+    ) -> Result<Self, ArtifactError> {
+        Ok(Self {
+            input,
+            vkey_hash: vk.bytes32(),
+            vk: vk.clone(),
+            public_values: PublicValues::abi_decode(proof.public_values.as_slice())?,
+            proof,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ArtifactError> {
+        let name = path.display().to_string();
+        let bytes = bincode::serialize(self).map_err(|err| ArtifactError::Encoding(name, err))?;
+        fs::write(path, bytes).map_err(|err| ArtifactError::Io(path.display().to_string(), err))
+    }
+
+    /// Reads an artifact and checks that its recorded vkey hash and public values are those
+    /// of its vkey and proof.
+    pub fn load(path: &Path) -> Result<Self, ArtifactError> {
+        let name = path.display().to_string();
+        let bytes = fs::read(path).map_err(|err| ArtifactError::Io(name.clone(), err))?;
+        let artifact: Self =
+            bincode::deserialize(&bytes).map_err(|err| ArtifactError::Encoding(name, err))?;
+        let actual = artifact.vk.bytes32();
+        if actual != artifact.vkey_hash {
+            return Err(ArtifactError::VkeyMismatch {
+                recorded: artifact.vkey_hash,
+                actual,
+            });
+        }
+        if PublicValues::abi_decode(artifact.proof.public_values.as_slice())?
+            != artifact.public_values
+        {
+            return Err(ArtifactError::PublicValuesMismatch);
+        }
+        Ok(artifact)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use alloy_primitives::B256;
+    use hdp_lib::{hash::HashId, outcome::Outcome};
+    use sp1_sdk::{ProverClient, SP1Proof, SP1PublicValues, SP1Stdin};
+
+    use super::*;
+    use crate::elf;
+
+    fn artifact() -> ProofArtifact {
+        let public_values = PublicValues {
+            hash_id: HashId::Keccak,
+            mmr_root: B256::with_last_byte(1),
+            mmr_size: 1,
+            outcome: Outcome::Valid,
+            headers: vec![],
+            diffs: vec![],
+        };
+        let (_, vk) = ProverClient::mock().setup(elf(HashId::Keccak));
+        let proof = SP1ProofWithPublicValues {
+            proof: SP1Proof::Core(vec![]),
+            stdin: SP1Stdin::new(),
+            public_values: SP1PublicValues::from(&public_values.abi_encode()),
+            sp1_version: String::new(),
+        };
+        ProofArtifact::new("keccak-test".to_string(), &vk, proof).unwrap()
+    }
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}-{}.proof", std::process::id()))
+    }
+
+    fn saved_and_loaded(
+        artifact: &ProofArtifact,
+        name: &str,
+    ) -> Result<ProofArtifact, ArtifactError> {
+        let path = path(name);
+        artifact.save(&path).unwrap();
+        let loaded = ProofArtifact::load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let artifact = artifact();
+        let loaded = saved_and_loaded(&artifact, "roundtrip").unwrap();
+        assert_eq!(loaded.input, "keccak-test");
+        assert_eq!(loaded.vkey_hash, artifact.vk.bytes32());
+        assert_eq!(loaded.vk.bytes32(), artifact.vk.bytes32());
+        assert_eq!(loaded.public_values, artifact.public_values);
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let mut tampered = artifact();
+        tampered.public_values.outcome = Outcome::MmrRootMismatch;
+        assert!(matches!(
+            saved_and_loaded(&tampered, "public-values"),
+            Err(ArtifactError::PublicValuesMismatch)
+        ));
+
+        let mut tampered = artifact();
+        tampered.vkey_hash = B256::ZERO.to_string();
+        assert!(matches!(
+            saved_and_loaded(&tampered, "vkey"),
+            Err(ArtifactError::VkeyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_not_an_artifact() {
+        let path = path("encoding");
+        artifact().save(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        for corrupt in [&bytes[..bytes.len() / 2], b"not a proof"] {
+            fs::write(&path, corrupt).unwrap();
+            assert!(matches!(
+                ProofArtifact::load(&path),
+                Err(ArtifactError::Encoding(..))
+            ));
+        }
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            ProofArtifact::load(&path),
+            Err(ArtifactError::Io(..))
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+//! Cycles per item of synthetic inputs, to compare the MMR hashers as inputs grow.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use hdp_lib::hash::HashId;
+use serde::{Deserialize, Serialize};
+
+use crate::synthetic::Spec;
+
+/// The part of a [`Spec`] a bench sweeps over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum Dimension {
+    Blocks,
+    MmrLeaves,
+    Accounts,
+    Slots,
+}
+
+impl Dimension {
+    /// `spec` with this dimension set to `size`, growing the MMR to fit the blocks.
+    pub fn apply(self, spec: &Spec, size: usize) -> Spec {
+        let mut spec = *spec;
+        match self {
+            Self::Blocks => spec.blocks = size,
+            Self::MmrLeaves => spec.mmr_leaves = size,
+            Self::Accounts => spec.accounts = size,
+            Self::Slots => spec.slots = size,
+        }
+        spec.mmr_leaves = spec.mmr_leaves.max(spec.blocks);
+        spec
+    }
+}
+
+/// Cycles of one synthetic input, and of each section divided by the items it works on:
+/// headers for `mmr` and `rlp`, proofs for `account mpt` and `storage mpt`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchRow {
+    pub hash_id: HashId,
+    pub blocks: usize,
+    pub mmr_leaves: usize,
+    pub accounts: usize,
+    pub slots: usize,
+    pub total: u64,
+    pub per_item: BTreeMap<String, f64>,
+}
+
+impl BenchRow {
+    pub fn new(spec: &Spec, hash_id: HashId, total: u64, sections: &HashMap<String, u64>) -> Self {
+        let per_item = sections
+            .iter()
+            .filter_map(|(name, cycles)| {
+                let items = match name.as_str() {
+                    "mmr" | "rlp" => spec.blocks,
+                    "account mpt" => spec.account_proofs(),
+                    "storage mpt" => spec.storage_proofs(),
+                    _ => return None,
+                };
+                (items > 0).then(|| (name.clone(), *cycles as f64 / items as f64))
+            })
+            .collect();
+        Self {
+            hash_id,
+            blocks: spec.blocks,
+            mmr_leaves: spec.mmr_leaves,
+            accounts: spec.accounts,
+            slots: spec.slots,
+            total,
+            per_item,
+        }
+    }
+}
+
+const SECTIONS: [&str; 4] = ["mmr", "rlp", "account mpt", "storage mpt"];
+
+/// One line per row, with the cycles per item of every section.
+pub fn table(rows: &[BenchRow]) -> String {
+    let mut table = format!(
+        "{:<8}  {:>6}  {:>10}  {:>8}  {:>5}  {:>12}",
+        "hash", "blocks", "mmr leaves", "accounts", "slots", "total"
+    );
+    for section in SECTIONS {
+        let _ = write!(table, "  {:>14}", format!("{section}/item"));
+    }
+    for row in rows {
+        let _ = write!(
+            table,
+            "\n{:<8}  {:>6}  {:>10}  {:>8}  {:>5}  {:>12}",
+            format!("{:?}", row.hash_id).to_lowercase(),
+            row.blocks,
+            row.mmr_leaves,
+            row.accounts,
+            row.slots,
+            row.total
+        );
+        for section in SECTIONS {
+            match row.per_item.get(section) {
+                Some(cycles) => write!(table, "  {cycles:>14.0}"),
+                None => write!(table, "  {:>14}", "-"),
+            }
+            .unwrap();
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: Spec = Spec {
+        blocks: 4,
+        mmr_leaves: 16,
+        accounts: 2,
+        slots: 3,
+    };
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(Dimension::Slots.apply(&SPEC, 5).slots, 5);
+        let spec = Dimension::Blocks.apply(&SPEC, 32);
+        assert_eq!((spec.blocks, spec.mmr_leaves), (32, 32));
+    }
+
+    #[test]
+    fn test_cycles_per_item() {
+        let sections = HashMap::from([
+            ("mmr".to_string(), 4000),
+            ("rlp".to_string(), 800),
+            ("account mpt".to_string(), 16000),
+            ("storage mpt".to_string(), 48000),
+            ("diff".to_string(), 10),
+        ]);
+        let row = BenchRow::new(&SPEC, HashId::Poseidon, 100000, &sections);
+        assert_eq!(
+            row.per_item.into_iter().collect::<Vec<_>>(),
+            [
+                ("account mpt".to_string(), 2000.0),
+                ("mmr".to_string(), 1000.0),
+                ("rlp".to_string(), 200.0),
+                ("storage mpt".to_string(), 2000.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! An end-to-end example of using the SP1 SDK to generate a proof of a program that can have an
+//! EVM-Compatible proof generated which can be verified on-chain.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin evm -- --system plonk
+//! ```
+//! or, without generating a real proof, for use with `SP1MockVerifier`:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin evm -- --mock
+//! ```
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use fibonacci_script::input::{load, NamedInput};
+use hdp_lib::verify::PublicValues;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+/// The arguments for the EVM command.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct EVMArgs {
+    /// Input JSON file, directory of JSON files, or `-` for stdin.
+    #[clap(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/keccak-test.json"))]
+    input: String,
+
+    #[clap(long, value_enum, default_value = "plonk")]
+    system: ProofSystem,
+
+    /// Use SP1's mock prover: the fixture carries real public values but an empty proof,
+    /// which only `SP1MockVerifier` accepts.
+    #[clap(long)]
+    mock: bool,
+
+    /// Directory the fixtures are written to.
+    #[clap(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../contracts/src/fixtures"))]
+    out: PathBuf,
+}
+
+/// Enum representing the available proof systems
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SP1HdpProofFixture {
+    /// The MMR the proven headers are in, which the consumer has to trust.
+    mmr_root: String,
+    mmr_size: u128,
+    /// Code of the verification outcome, `0` when valid.
+    outcome: u8,
+    headers: usize,
+    diffs: usize,
+    vkey: String,
+    public_values: String,
+    proof: String,
+}
+
+fn main() {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+
+    // Parse the command line arguments.
+    let args = EVMArgs::parse();
+
+    let inputs = load(&args.input).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    // Setup the prover client.
+    let client = if args.mock {
+        ProverClient::mock()
+    } else {
+        ProverClient::new()
+    };
+
+    for input in inputs {
+        // Setup the program.
+        let (pk, vk) = client.setup(input.input.elf());
+
+        println!("input: {}", input.name);
+        println!("Proof System: {:?}", args.system);
+
+        // Generate the proof based on the selected proof system.
+        let prover = client.prove(&pk, input.input.stdin());
+        let proof = match args.system {
+            ProofSystem::Plonk => prover.plonk().run(),
+            ProofSystem::Groth16 => prover.groth16().run(),
+        }
+        .expect("failed to generate proof");
+
+        create_proof_fixture(&proof, &vk, &args, &input);
+    }
+}
+
+/// Create a fixture for the given proof.
+fn create_proof_fixture(
+    proof: &SP1ProofWithPublicValues,
+    vk: &SP1VerifyingKey,
+    args: &EVMArgs,
+    input: &NamedInput,
+) {
+    // Deserialize the public values.
+    let bytes = proof.public_values.as_slice();
+    let public_values = PublicValues::abi_decode(bytes).expect("invalid public values");
+    println!("outcome: {:?}", public_values.outcome);
+
+    // `SP1MockVerifier` only accepts an empty proof.
+    let proof_bytes = if args.mock { vec![] } else { proof.bytes() };
+
+    // Create the testing fixture so we can test things end-to-end.
+    let fixture = SP1HdpProofFixture {
+        mmr_root: public_values.mmr_root.to_string(),
+        mmr_size: public_values.mmr_size,
+        outcome: public_values.outcome.to_parts().0,
+        headers: public_values.headers.len(),
+        diffs: public_values.diffs.len(),
+        vkey: vk.bytes32().to_string(),
+        public_values: format!("0x{}", hex::encode(bytes)),
+        proof: format!("0x{}", hex::encode(proof_bytes)),
+    };
+
+    // The verification key is used to verify that the proof corresponds to the execution of the
+    // program on the given input.
+    //
+    // Note that the verification key stays the same regardless of the input.
+    println!("Verification Key: {}", fixture.vkey);
+
+    // The public values are the values which are publicly committed to by the zkVM.
+    //
+    // If you need to expose the inputs or outputs of your program, you should commit them in
+    // the public values.
+    println!("Public Values: {}", fixture.public_values);
+
+    // The proof proves to the verifier that the program was executed with some inputs that led
+    // to the give public values.
+    println!("Proof Bytes: {}", fixture.proof);
+
+    // Save the fixture to a file, named after the input and the proof system.
+    let system = format!("{:?}", args.system).to_lowercase();
+    std::fs::create_dir_all(&args.out).expect("failed to create fixture path");
+    let path = args
+        .out
+        .join(format!("{}-{system}-fixture.json", input.stem()));
+    std::fs::write(&path, serde_json::to_string_pretty(&fixture).unwrap())
+        .expect("failed to write fixture");
+    println!("Fixture written to {}", path.display());
+}
@@ -9,19 +9,54 @@
 //! ```shell
 //! RUST_LOG=info cargo run --release -- --prove
 //! ```
+//! Inputs default to `keccak-test.json`; pass `--input <file|dir|->` to use others.
+//!
+//! Synthetic inputs can be generated, or benchmarked for both MMR hashers, with:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- generate --hash poseidon --blocks 8 --out input.json
+//! RUST_LOG=info cargo run --release -- bench --sweep blocks --sizes 1,4,16
+//! ```
+//!
+//! Inputs can be fetched from a node, with headers proven by an MMR store file:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- fetch --rpc-url $ETH_RPC_URL --mmr-store mmr.json \
+//!     --block 5641516 --address 0x75cec1db9dceb703200eaa6595f66885c962b920 --slot 0x1
+//! ```
+//!
+//! An input can be decoded and checked with:
+//! ```shell
+//! cargo run --release -- inspect keccak-test.json
+//! ```
+//!
+//! Proofs are saved to `proofs/`, and can be checked again with:
+//! ```shell
+//! RUST_LOG=info cargo run --release -- verify --proof proofs/keccak-test.proof
+//! ```
 
-use clap::Parser;
-use hdp_lib::json::MmrJson;
-use sp1_sdk::{ProverClient, SP1Stdin};
+use std::path::{Path, PathBuf};
 
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_bytes!("../../../elf/riscv32im-succinct-zkvm-elf");
-pub const MMR_KECCAK_FIXTURE: &str = include_str!("../../keccak-test.json");
+use alloy_primitives::{Address, B256};
+use clap::{Parser, Subcommand};
+use fibonacci_script::{
+    artifact::ProofArtifact,
+    bench::{table, BenchRow, Dimension},
+    cycles::CycleReport,
+    fetch::{fetch, FetchRequest, Store},
+    input::{load, Input, InputError, NamedInput},
+    inspect::inspect,
+    rpc::RpcClient,
+    synthetic::{generate, Spec},
+};
+use hdp_lib::{hash::HashId, verify::PublicValues};
+use sp1_sdk::ProverClient;
 
 /// The arguments for the command.
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     #[clap(long)]
     execute: bool,
 
@@ -30,6 +65,92 @@ struct Args {
 
     #[clap(long, default_value = "20")]
     n: u32,
+
+    /// Input JSON file, directory of JSON files, or `-` for stdin.
+    #[clap(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/keccak-test.json"))]
+    input: String,
+
+    /// Write the `--execute` cycle counts per section to this file as JSON.
+    #[clap(long)]
+    cycles_out: Option<PathBuf>,
+
+    /// Directory `--prove` saves proof artifacts to.
+    #[clap(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/proofs"))]
+    proof_dir: PathBuf,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Verify a proof saved by `--prove` again and print its public values.
+    Verify {
+        #[clap(long)]
+        proof: PathBuf,
+    },
+
+    /// Decode and print an input, listing what would make the guest reject it.
+    Inspect { file: String },
+
+    /// Write a synthetic input of the given shape.
+    Generate {
+        #[clap(long, default_value = "keccak")]
+        hash: HashId,
+
+        #[clap(flatten)]
+        spec: Spec,
+
+        /// File to write the input to, stdout if not given.
+        #[clap(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Fetch headers and proofs from a node into an input.
+    Fetch {
+        #[clap(long, env = "ETH_RPC_URL")]
+        rpc_url: String,
+
+        /// MMR store file the headers are proven with.
+        #[clap(long)]
+        mmr_store: String,
+
+        #[clap(long = "block", value_delimiter = ',', required = true)]
+        blocks: Vec<u64>,
+
+        /// Accounts proven at every block.
+        #[clap(long = "address", value_delimiter = ',')]
+        addresses: Vec<Address>,
+
+        /// Slots proven for every account at every block.
+        #[clap(long = "slot", value_delimiter = ',')]
+        slots: Vec<B256>,
+
+        /// File to write the input to, stdout if not given.
+        #[clap(long)]
+        out: Option<PathBuf>,
+
+        /// Also save the node's responses, to replay them with `mock_rpc`.
+        #[clap(long)]
+        record: Option<PathBuf>,
+    },
+
+    /// Execute synthetic inputs of growing size and report cycles per item for each hasher.
+    Bench {
+        #[clap(long, value_enum, default_value = "blocks")]
+        sweep: Dimension,
+
+        /// Values the swept dimension takes.
+        #[clap(long, value_delimiter = ',', default_value = "1,2,4,8")]
+        sizes: Vec<usize>,
+
+        #[clap(long, value_delimiter = ',', default_value = "keccak,poseidon")]
+        hashes: Vec<HashId>,
+
+        #[clap(flatten)]
+        spec: Spec,
+
+        /// Also write the rows to this file as JSON.
+        #[clap(long)]
+        out: Option<PathBuf>,
+    },
 }
 
 fn main() {
@@ -39,53 +160,212 @@ fn main() {
     // Parse the command line arguments.
     let args = Args::parse();
 
+    if let Some(command) = args.command {
+        match command {
+            Command::Verify { proof } => verify(&proof),
+            Command::Inspect { file } => {
+                let json = std::fs::read_to_string(&file)
+                    .map_err(|err| InputError::Io(file.clone(), err))
+                    .unwrap_or_else(exit);
+                let input = Input::from_json(&json)
+                    .map_err(|errors| InputError::Invalid {
+                        name: file.clone(),
+                        errors,
+                    })
+                    .unwrap_or_else(exit);
+                let inspection = inspect(&input);
+                println!("{file}\n{inspection}");
+                if !inspection.issues.is_empty() {
+                    std::process::exit(1);
+                }
+            }
+            Command::Generate { hash, spec, out } => {
+                let input = generate(&spec, hash).unwrap_or_else(exit);
+                write_input(&input.to_json().unwrap(), out.as_deref())
+            }
+            Command::Fetch {
+                rpc_url,
+                mmr_store,
+                blocks,
+                addresses,
+                slots,
+                out,
+                record,
+            } => {
+                let store = Store::load(&mmr_store).unwrap_or_else(exit);
+                let request = FetchRequest {
+                    blocks,
+                    addresses,
+                    slots,
+                };
+                let mut rpc = RpcClient::new(rpc_url);
+                if record.is_some() {
+                    rpc = rpc.recording();
+                }
+                let input = fetch(&mut rpc, store, &request).unwrap_or_else(exit);
+                if let Some(path) = record {
+                    rpc.save_recording(&path).unwrap_or_else(exit);
+                }
+                let errors = input.validate();
+                if !errors.is_empty() {
+                    exit(InputError::Invalid {
+                        name: "fetched input".to_string(),
+                        errors,
+                    })
+                }
+                write_input(&input.to_json().unwrap(), out.as_deref());
+            }
+            Command::Bench {
+                sweep,
+                sizes,
+                hashes,
+                spec,
+                out,
+            } => bench(sweep, &sizes, &hashes, &spec, out.as_deref()),
+        }
+        return;
+    }
+
     if args.execute == args.prove {
         eprintln!("Error: You must specify either --execute or --prove");
         std::process::exit(1);
     }
 
+    // Read and check every input before anything is sent to the prover.
+    let inputs = load(&args.input).unwrap_or_else(exit);
+
     // Setup the prover client.
     let client = ProverClient::new();
 
-    // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    let fixture: MmrJson = serde_json::from_str(MMR_KECCAK_FIXTURE).unwrap();
+    let mut cycles = CycleReport::default();
+    for named in inputs {
+        let NamedInput { name, input } = &named;
+        println!("input: {name} ({:?} mmr)", input.hash_id());
+        let elf = input.elf();
+        let stdin = input.stdin();
+
+        if args.execute {
+            // Execute the program
+            let (output, report) = client.execute(elf, stdin).run().unwrap();
+            println!("Program executed successfully.");
 
-    stdin.write(&fixture.accounts[0]);
-    stdin.write(&fixture.storages[0]);
-    stdin.write(&fixture.headers);
-    stdin.write(&fixture.meta);
+            let public_values = PublicValues::abi_decode(output.as_slice()).unwrap();
+            print_public_values(&public_values);
 
-    // println!("n: {}", args.n);
+            // Record the number of cycles executed.
+            println!("Number of cycles: {}", report.total_instruction_count());
+            cycles.add(
+                name,
+                report.total_instruction_count(),
+                &report.cycle_tracker,
+            );
+        } else {
+            // Setup the program for proving.
+            let (pk, vk) = client.setup(elf);
+
+            // Generate the proof
+            let proof = client
+                .prove(&pk, stdin)
+                .run()
+                .expect("failed to generate proof");
+
+            println!("Successfully generated proof!");
+
+            // Verify the proof.
+            client.verify(&proof, &vk).expect("failed to verify proof");
+            println!("Successfully verified proof!");
+
+            // Save the proof so it can be verified again later.
+            let artifact = ProofArtifact::new(name.clone(), &vk, proof).unwrap_or_else(exit);
+            print_public_values(&artifact.public_values);
+            std::fs::create_dir_all(&args.proof_dir).expect("failed to create proof dir");
+            let path = args.proof_dir.join(format!("{}.proof", named.stem()));
+            artifact.save(&path).unwrap_or_else(exit);
+            println!("Proof saved to {}", path.display());
+        }
+    }
 
     if args.execute {
-        // Execute the program
-        let (output, report) = client.execute(FIBONACCI_ELF, stdin).run().unwrap();
-        println!("Program executed successfully.");
-
-        println!("result: {:?}", output);
-
-        // let (expected_a, expected_b) = fibonacci_lib::fibonacci(n);
-        // assert_eq!(a, expected_a);
-        // assert_eq!(b, expected_b);
-        // println!("Values are correct!");
-
-        // Record the number of cycles executed.
-        println!("Number of cycles: {}", report.total_instruction_count());
-    } else {
-        // Setup the program for proving.
-        let (pk, vk) = client.setup(FIBONACCI_ELF);
-
-        // Generate the proof
-        let proof = client
-            .prove(&pk, stdin)
-            .run()
-            .expect("failed to generate proof");
-
-        println!("Successfully generated proof!");
-
-        // Verify the proof.
-        client.verify(&proof, &vk).expect("failed to verify proof");
-        println!("Successfully verified proof!");
+        println!("{cycles}");
+        if let Some(path) = &args.cycles_out {
+            cycles.save(path).unwrap_or_else(exit);
+            println!("Cycle report written to {}", path.display());
+        }
     }
 }
+
+/// Verifies a saved proof against the verifying key saved with it.
+fn verify(path: &Path) {
+    let artifact = ProofArtifact::load(path).unwrap_or_else(exit);
+    println!("input: {}", artifact.input);
+    println!("vkey: {}", artifact.vkey_hash);
+
+    ProverClient::new()
+        .verify(&artifact.proof, &artifact.vk)
+        .expect("failed to verify proof");
+    println!("Successfully verified proof!");
+    print_public_values(&artifact.public_values);
+}
+
+/// Executes a synthetic input per size and hasher, and prints the cycles per item.
+fn bench(sweep: Dimension, sizes: &[usize], hashes: &[HashId], spec: &Spec, out: Option<&Path>) {
+    let specs: Vec<Spec> = sizes.iter().map(|size| sweep.apply(spec, *size)).collect();
+    specs
+        .iter()
+        .try_for_each(Spec::validate)
+        .unwrap_or_else(exit);
+
+    let client = ProverClient::new();
+    let mut rows = vec![];
+    for spec in specs {
+        for hash_id in hashes {
+            println!("{hash_id:?} mmr: {spec:?}");
+            let input = generate(&spec, *hash_id).unwrap_or_else(exit);
+            let (output, report) = client.execute(input.elf(), input.stdin()).run().unwrap();
+            let public_values = PublicValues::abi_decode(output.as_slice()).unwrap();
+            if !public_values.outcome.is_valid() {
+                eprintln!(
+                    "Error: invalid synthetic input: {:?}",
+                    public_values.outcome
+                );
+                std::process::exit(1);
+            }
+            rows.push(BenchRow::new(
+                &spec,
+                *hash_id,
+                report.total_instruction_count(),
+                &report.cycle_tracker,
+            ));
+        }
+    }
+
+    println!("{}", table(&rows));
+    if let Some(path) = out {
+        std::fs::write(path, serde_json::to_string_pretty(&rows).unwrap()).unwrap_or_else(exit);
+        println!("Bench written to {}", path.display());
+    }
+}
+
+fn write_input(json: &str, out: Option<&Path>) {
+    match out {
+        Some(path) => std::fs::write(path, json).unwrap_or_else(exit),
+        None => println!("{json}"),
+    }
+}
+
+fn print_public_values(public_values: &PublicValues) {
+    println!(
+        "mmr: root {}, size {}",
+        public_values.mmr_root, public_values.mmr_size
+    );
+    println!("outcome: {:?}", public_values.outcome);
+    for header in &public_values.headers {
+        println!("header: block {}, hash {}", header.number, header.hash);
+    }
+    println!("diffs: {:?}", public_values.diffs);
+}
+
+fn exit<T>(err: impl std::fmt::Display) -> T {
+    eprintln!("Error: {err}");
+    std::process::exit(1);
+}
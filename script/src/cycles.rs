@@ -0,0 +1,136 @@
+//! Cycle counts of the guest sections marked with `cycle_tracker!`, summed over every
+//! execution so runs can be compared against each other.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Cycles of one execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct InputCycles {
+    pub input: String,
+    pub total: u64,
+    pub sections: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct CycleReport {
+    pub total: u64,
+    /// Cycles per section, summed over every execution.
+    pub sections: BTreeMap<String, u64>,
+    pub inputs: Vec<InputCycles>,
+}
+
+impl CycleReport {
+    /// Adds an execution of `total` cycles, with the counts of its tracked `sections`.
+    pub fn add<'a>(
+        &mut self,
+        input: &str,
+        total: u64,
+        sections: impl IntoIterator<Item = (&'a String, &'a u64)>,
+    ) {
+        let sections: BTreeMap<_, _> = sections
+            .into_iter()
+            .map(|(name, cycles)| (name.clone(), *cycles))
+            .collect();
+        for (name, cycles) in &sections {
+            *self.sections.entry(name.clone()).or_default() += cycles;
+        }
+        self.total += total;
+        self.inputs.push(InputCycles {
+            input: input.to_string(),
+            total,
+            sections,
+        });
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+/// A table of the sections, their cycles and their share of the total.
+impl fmt::Display for CycleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .sections
+            .iter()
+            .map(|(name, cycles)| (name.as_str(), *cycles))
+            .chain([("total", self.total)]);
+        let width = rows
+            .clone()
+            .map(|(name, _)| name.len())
+            .chain(["section".len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut table = format!("{:<width$}  {:>12}  {:>7}\n", "section", "cycles", "share");
+        for (name, cycles) in rows {
+            let share = match self.total {
+                0 => 0.0,
+                total => cycles as f64 * 100.0 / total as f64,
+            };
+            let _ = writeln!(table, "{name:<width$}  {cycles:>12}  {share:>6.2}%");
+        }
+        f.write_str(table.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn tracker(sections: &[(&str, u64)]) -> HashMap<String, u64> {
+        sections
+            .iter()
+            .map(|(name, cycles)| (name.to_string(), *cycles))
+            .collect()
+    }
+
+    #[test]
+    fn test_sections_are_summed() {
+        let mut report = CycleReport::default();
+        report.add("a.json", 1000, &tracker(&[("mmr", 300), ("rlp", 100)]));
+        report.add(
+            "b.json",
+            600,
+            &tracker(&[("mmr", 200), ("account mpt", 50)]),
+        );
+
+        assert_eq!(report.total, 1600);
+        assert_eq!(
+            report.sections.into_iter().collect::<Vec<_>>(),
+            [
+                ("account mpt".to_string(), 50),
+                ("mmr".to_string(), 500),
+                ("rlp".to_string(), 100),
+            ]
+        );
+        assert_eq!(report.inputs[1].sections["mmr"], 200);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut report = CycleReport::default();
+        report.add(
+            "a.json",
+            1000,
+            &tracker(&[("mmr", 250), ("account mpt", 500)]),
+        );
+        assert_eq!(
+            report.to_string(),
+            "section            cycles    share\n\
+             account mpt           500   50.00%\n\
+             mmr                   250   25.00%\n\
+             total                1000  100.00%"
+        );
+    }
+}
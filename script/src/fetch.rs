@@ -0,0 +1,340 @@
+//! Builds an input from a node: headers from `eth_getBlockByNumber`, account and storage
+//! proofs from `eth_getProof`, and the MMR they are proven against from a store file.
+
+use std::{collections::BTreeMap, fs};
+
+use alloy_primitives::{keccak256, Address, B256};
+use hdp_lib::{
+    account::HdpAccount,
+    eip1186::{from_responses, BlockProofResponse, Eip1186Error},
+    hash::HashId,
+    json::MmrJson,
+    mmr, mmr_keccak,
+    storage::HdpStorage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{parse, Input, InputError, InputHash},
+    rpc::{RpcClient, RpcError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+
+    #[error(transparent)]
+    Eip1186(#[from] Eip1186Error),
+
+    #[error("block {0}: {1}")]
+    Header(u64, String),
+
+    #[error("block {block}: header does not hash to {hash}")]
+    HeaderHashMismatch { block: u64, hash: B256 },
+
+    #[error("block {0} is not in the mmr store")]
+    NotInStore(u64),
+}
+
+/// An MMR and the inclusion proofs of the blocks it holds, keyed by block number.
+#[derive(Serialize, Deserialize)]
+pub struct MmrStore<M = mmr_keccak::MmrMeta, P = mmr_keccak::HeaderInclusionProof> {
+    #[serde(default)]
+    pub hash_id: HashId,
+    pub meta: M,
+    pub proofs: BTreeMap<u64, P>,
+}
+
+pub type PoseidonMmrStore = MmrStore<mmr::MmrMeta, mmr::HeaderInclusionProof>;
+
+impl<M, P> MmrStore<M, P> {
+    fn into_input<H>(
+        mut self,
+        headers: Vec<(u64, Vec<u8>)>,
+        accounts: Vec<HdpAccount>,
+        storages: Vec<HdpStorage>,
+        header: impl Fn(&[u8], P) -> H,
+    ) -> Result<MmrJson<M, H>, FetchError> {
+        let headers = headers
+            .into_iter()
+            .map(|(block, rlp)| {
+                let proof = self
+                    .proofs
+                    .remove(&block)
+                    .ok_or(FetchError::NotInStore(block))?;
+                Ok(header(&rlp, proof))
+            })
+            .collect::<Result<_, FetchError>>()?;
+        Ok(MmrJson {
+            hash_id: self.hash_id,
+            meta: self.meta,
+            headers,
+            accounts,
+            storages,
+            diffs: vec![],
+        })
+    }
+}
+
+/// A store, with the MMR types of the hasher it names.
+pub enum Store {
+    Keccak(MmrStore),
+    Poseidon(PoseidonMmrStore),
+}
+
+impl Store {
+    pub fn load(path: &str) -> Result<Self, InputError> {
+        let json = fs::read_to_string(path).map_err(|err| InputError::Io(path.to_string(), err))?;
+        Self::parse(&json).map_err(|errors| InputError::Invalid {
+            name: path.to_string(),
+            errors,
+        })
+    }
+
+    pub fn parse(json: &str) -> Result<Self, Vec<crate::input::FieldError>> {
+        Ok(match parse::<InputHash>(json)?.hash_id {
+            HashId::Keccak => Self::Keccak(parse(json)?),
+            HashId::Poseidon => Self::Poseidon(parse(json)?),
+        })
+    }
+
+    fn contains(&self, block: u64) -> bool {
+        match self {
+            Self::Keccak(store) => store.proofs.contains_key(&block),
+            Self::Poseidon(store) => store.proofs.contains_key(&block),
+        }
+    }
+}
+
+/// What to fetch: every account with every slot, at every block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchRequest {
+    pub blocks: Vec<u64>,
+    pub addresses: Vec<Address>,
+    pub slots: Vec<B256>,
+}
+
+/// Fetches the headers and proofs of `request` and proves the headers with `store`.
+pub fn fetch(
+    rpc: &mut RpcClient,
+    store: Store,
+    request: &FetchRequest,
+) -> Result<Input, FetchError> {
+    let mut blocks = request.blocks.clone();
+    blocks.sort_unstable();
+    blocks.dedup();
+    // before any call, as a block the store cannot prove makes the rest useless
+    if let Some(&block) = blocks.iter().find(|block| !store.contains(**block)) {
+        return Err(FetchError::NotInStore(block));
+    }
+
+    let mut headers = vec![];
+    for &block in &blocks {
+        let header = rpc.header(block)?;
+        let hash = header.hash;
+        let header = alloy_consensus::Header::try_from(header)
+            .map_err(|err| FetchError::Header(block, err.to_string()))?;
+        let rlp = alloy_rlp::encode(&header);
+        if keccak256(&rlp) != hash {
+            return Err(FetchError::HeaderHashMismatch { block, hash });
+        }
+        headers.push((block, rlp));
+    }
+
+    let mut accounts = vec![];
+    let mut storages = vec![];
+    for &address in &request.addresses {
+        let responses = blocks
+            .iter()
+            .map(|&block_number| {
+                Ok(BlockProofResponse {
+                    block_number,
+                    response: rpc.proof(address, &request.slots, block_number)?,
+                })
+            })
+            .collect::<Result<Vec<_>, RpcError>>()?;
+        let (account, slots) = from_responses(responses)?;
+        accounts.push(account);
+        storages.extend(slots);
+    }
+
+    Ok(match store {
+        Store::Keccak(store) => Input::Keccak(store.into_input(
+            headers,
+            accounts,
+            storages,
+            mmr_keccak::Header::from_rlp_bytes,
+        )?),
+        Store::Poseidon(store) => Input::Poseidon(store.into_input(
+            headers,
+            accounts,
+            storages,
+            mmr::Header::from_rlp_bytes,
+        )?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_rpc_types_eth::{EIP1186AccountProofResponse, EIP1186StorageProof, Header};
+    use hdp_lib::rlp::decode_header;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        mock_rpc::MockRpcServer,
+        rpc::{block_params, proof_params, Exchange},
+        synthetic::{generate, Spec},
+    };
+
+    const SPEC: Spec = Spec {
+        blocks: 2,
+        mmr_leaves: 6,
+        accounts: 2,
+        slots: 2,
+    };
+
+    /// What a node holding the synthetic chain would answer, and the store of its MMR.
+    fn recorded(input: &MmrJson) -> (Vec<Exchange>, String, FetchRequest) {
+        let mut exchanges = vec![];
+        let mut proofs = BTreeMap::new();
+        let mut request = FetchRequest::default();
+        for header in &input.headers {
+            let decoded = decode_header(&header.rlp_bytes().unwrap()).unwrap();
+            let rpc_header = Header {
+                hash: decoded.hash_slow(),
+                parent_hash: decoded.parent_hash,
+                uncles_hash: decoded.ommers_hash,
+                miner: decoded.beneficiary,
+                state_root: decoded.state_root,
+                transactions_root: decoded.transactions_root,
+                receipts_root: decoded.receipts_root,
+                logs_bloom: decoded.logs_bloom,
+                difficulty: decoded.difficulty,
+                number: decoded.number,
+                gas_limit: decoded.gas_limit.into(),
+                gas_used: decoded.gas_used.into(),
+                timestamp: decoded.timestamp,
+                extra_data: decoded.extra_data.clone(),
+                mix_hash: Some(decoded.mix_hash),
+                nonce: Some(decoded.nonce.to_be_bytes().into()),
+                ..Default::default()
+            };
+            let (method, params) = block_params(decoded.number);
+            exchanges.push(Exchange {
+                method: method.to_string(),
+                params,
+                result: serde_json::to_value(rpc_header).unwrap(),
+            });
+            proofs.insert(decoded.number, serde_json::to_value(&header.proof).unwrap());
+            request.blocks.push(decoded.number);
+        }
+
+        request.slots = input.storages[..SPEC.slots]
+            .iter()
+            .map(|storage| storage.slot)
+            .collect();
+        for account in &input.accounts {
+            request.addresses.push(account.address);
+            for (proof, claim) in account.proofs.iter().zip(&account.claims) {
+                let block = proof.block_number;
+                let response = EIP1186AccountProofResponse {
+                    address: account.address,
+                    balance: claim.balance,
+                    code_hash: claim.code_hash,
+                    nonce: claim.nonce,
+                    storage_hash: claim.storage_root,
+                    account_proof: proof.proof.clone(),
+                    storage_proof: input
+                        .storages
+                        .iter()
+                        .filter(|storage| storage.address == account.address)
+                        .map(|storage| EIP1186StorageProof {
+                            key: storage.slot.into(),
+                            value: storage.claim_at(block).unwrap().value,
+                            proof: storage.proof_at(block).unwrap().to_vec(),
+                        })
+                        .collect(),
+                };
+                let (method, params) = proof_params(account.address, &request.slots, block);
+                exchanges.push(Exchange {
+                    method: method.to_string(),
+                    params,
+                    result: serde_json::to_value(response).unwrap(),
+                });
+            }
+        }
+
+        let store = json!({ "hash_id": "keccak", "meta": input.meta, "proofs": proofs });
+        (exchanges, store.to_string(), request)
+    }
+
+    fn synthetic() -> MmrJson {
+        match generate(&SPEC, HashId::Keccak).unwrap() {
+            Input::Keccak(input) => input,
+            Input::Poseidon(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_fetch_from_mock() {
+        let input = synthetic();
+        let (exchanges, store, request) = recorded(&input);
+        let server = MockRpcServer::start(exchanges.clone()).unwrap();
+
+        let mut rpc = RpcClient::new(server.url()).recording();
+        let store = Store::parse(&store).ok().unwrap();
+        let fetched = fetch(&mut rpc, store, &request).unwrap();
+
+        let json = |input: &Input| serde_json::from_str::<Value>(&input.to_json().unwrap());
+        assert_eq!(
+            json(&fetched).unwrap(),
+            json(&Input::Keccak(input)).unwrap()
+        );
+
+        // what was fetched can be replayed
+        let path = std::env::temp_dir().join(format!("fetch-{}.json", std::process::id()));
+        rpc.save_recording(&path).unwrap();
+        let recording: Vec<Exchange> =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(recording.len(), exchanges.len());
+        assert!(recording
+            .iter()
+            .all(|exchange| exchanges.contains(exchange)));
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let input = synthetic();
+        let (exchanges, store, mut request) = recorded(&input);
+        let server = MockRpcServer::start(exchanges).unwrap();
+        let mut rpc = RpcClient::new(server.url());
+
+        // a block the store proves but the node was not recorded for
+        let mut store: Value = serde_json::from_str(&store).unwrap();
+        let last = request.blocks.last().unwrap().to_string();
+        store["proofs"]["1"] = store["proofs"][&last].clone();
+        request.blocks.push(1);
+        let store_of = |json: &Value| Store::parse(&json.to_string()).ok().unwrap();
+        let err = fetch(&mut rpc, store_of(&store), &request).err().unwrap();
+        assert!(
+            matches!(&err, FetchError::Rpc(RpcError::Rpc { code: -32601, .. })),
+            "{err}"
+        );
+
+        // a block the store cannot prove, found before asking the node anything
+        request.blocks.pop();
+        store["proofs"].as_object_mut().unwrap().remove(&last);
+        let silent = MockRpcServer::start(vec![]).unwrap();
+        let err = fetch(
+            &mut RpcClient::new(silent.url()),
+            store_of(&store),
+            &request,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, FetchError::NotInStore(block) if block.to_string() == last));
+    }
+}
@@ -0,0 +1,407 @@
+//! Guest inputs read from a JSON file, a directory of JSON files or stdin, and checked
+//! field by field before they reach the prover.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use alloy_primitives::{hex, keccak256};
+use hdp_lib::{
+    hash::HashId,
+    json::{MmrJson, PoseidonMmrJson},
+    rlp::decode_header,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use sp1_sdk::SP1Stdin;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InputError {
+    #[error("{0}: {1}")]
+    Io(String, io::Error),
+
+    #[error("{0}: no .json files in directory")]
+    EmptyDirectory(String),
+
+    #[error("{name}: invalid input{}", list(.errors))]
+    Invalid {
+        name: String,
+        errors: Vec<FieldError>,
+    },
+}
+
+/// A problem with one field of an input, e.g. `accounts[0].proofs[1].block_number`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.field, self.message)
+    }
+}
+
+fn list(errors: &[FieldError]) -> String {
+    errors.iter().fold(String::new(), |mut list, error| {
+        let _ = write!(list, "\n  {error}");
+        list
+    })
+}
+
+/// A guest input, with the MMR types of the hasher it names.
+pub enum Input {
+    Keccak(MmrJson),
+    Poseidon(PoseidonMmrJson),
+}
+
+/// An input and where it was read from: its path, or `-` for stdin.
+pub struct NamedInput {
+    pub name: String,
+    pub input: Input,
+}
+
+/// Just enough of the input to pick the types of the rest.
+#[derive(Deserialize)]
+pub(crate) struct InputHash {
+    #[serde(default)]
+    pub(crate) hash_id: HashId,
+}
+
+impl Input {
+    /// Parses `json` and checks that its proofs refer to its own headers and accounts.
+    pub fn parse(json: &str) -> Result<Self, Vec<FieldError>> {
+        let input = Self::from_json(json)?;
+        let errors = input.validate();
+        if errors.is_empty() {
+            Ok(input)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parses `json` without any of the checks of [`Input::parse`].
+    pub fn from_json(json: &str) -> Result<Self, Vec<FieldError>> {
+        Ok(match parse::<InputHash>(json)?.hash_id {
+            HashId::Keccak => Self::Keccak(parse(json)?),
+            HashId::Poseidon => Self::Poseidon(parse(json)?),
+        })
+    }
+
+    pub fn hash_id(&self) -> HashId {
+        match self {
+            Self::Keccak(input) => input.hash_id,
+            Self::Poseidon(input) => input.hash_id,
+        }
+    }
+
+    /// The ELF built for the hasher of this input.
+    pub fn elf(&self) -> &'static [u8] {
+        crate::elf(self.hash_id())
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        match self {
+            Self::Keccak(input) => serde_json::to_string_pretty(input),
+            Self::Poseidon(input) => serde_json::to_string_pretty(input),
+        }
+    }
+
+    pub fn stdin(&self) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        match self {
+            Self::Keccak(input) => stdin.write(input),
+            Self::Poseidon(input) => stdin.write(input),
+        }
+        stdin
+    }
+
+    /// Checks that the proofs refer to the input's own headers and accounts.
+    pub fn validate(&self) -> Vec<FieldError> {
+        match self {
+            Self::Keccak(input) => validate(input, self.facts()),
+            Self::Poseidon(input) => validate(input, self.facts()),
+        }
+    }
+
+    pub(crate) fn facts(&self) -> MmrFacts {
+        match self {
+            Self::Keccak(input) => MmrFacts {
+                size: input.meta.size,
+                peaks: input.meta.peaks.len(),
+                headers: input
+                    .headers
+                    .iter()
+                    .map(|header| HeaderFacts {
+                        leaf_idx: header.proof.leaf_idx,
+                        path_len: header.proof.mmr_path.len(),
+                        rlp: header.rlp_bytes().map_err(|err| err.to_string()),
+                    })
+                    .collect(),
+            },
+            Self::Poseidon(input) => MmrFacts {
+                size: input.meta.size,
+                peaks: input.meta.peaks.len(),
+                headers: input
+                    .headers
+                    .iter()
+                    .map(|header| HeaderFacts {
+                        leaf_idx: header.proof.leaf_idx,
+                        path_len: header.proof.mmr_path.len(),
+                        rlp: header.rlp_bytes().map_err(|err| err.to_string()),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Reads the input at `path`: a JSON file, every `.json` file of a directory in name
+/// order, or stdin for `-`.
+pub fn load(path: &str) -> Result<Vec<NamedInput>, InputError> {
+    if path == "-" {
+        let mut json = String::new();
+        io::stdin()
+            .read_to_string(&mut json)
+            .map_err(|err| InputError::Io(path.to_string(), err))?;
+        return Ok(vec![named(path.to_string(), &json)?]);
+    }
+
+    let io_error = |err| InputError::Io(path.to_string(), err);
+    if !Path::new(path).is_dir() {
+        let json = fs::read_to_string(path).map_err(io_error)?;
+        return Ok(vec![named(path.to_string(), &json)?]);
+    }
+
+    let mut files = fs::read_dir(path)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error)?;
+    files.retain(|file| file.is_file() && file.extension().is_some_and(|ext| ext == "json"));
+    files.sort();
+    if files.is_empty() {
+        return Err(InputError::EmptyDirectory(path.to_string()));
+    }
+
+    files
+        .into_iter()
+        .map(|file| {
+            let name = file.display().to_string();
+            let json =
+                fs::read_to_string(&file).map_err(|err| InputError::Io(name.clone(), err))?;
+            named(name, &json)
+        })
+        .collect()
+}
+
+impl NamedInput {
+    /// File stem of the input, or `stdin`, to name what is generated from it.
+    pub fn stem(&self) -> String {
+        match Path::new(&self.name).file_stem() {
+            Some(stem) if self.name != "-" => stem.to_string_lossy().into_owned(),
+            _ => "stdin".to_string(),
+        }
+    }
+}
+
+fn named(name: String, json: &str) -> Result<NamedInput, InputError> {
+    match Input::parse(json) {
+        Ok(input) => Ok(NamedInput { name, input }),
+        Err(errors) => Err(InputError::Invalid { name, errors }),
+    }
+}
+
+/// Deserializes `json`, reporting the path of the field that failed.
+pub(crate) fn parse<T: DeserializeOwned>(json: &str) -> Result<T, Vec<FieldError>> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let field = match err.path().to_string() {
+            path if path == "." => "<root>".to_string(),
+            path => path,
+        };
+        vec![FieldError::new(field, err.inner().to_string())]
+    })
+}
+
+/// The parts of the MMR types the checks need, whichever hasher they are for. Nothing
+/// here is hashed, so validating stays cheap.
+pub(crate) struct MmrFacts {
+    pub(crate) size: u128,
+    pub(crate) peaks: usize,
+    pub(crate) headers: Vec<HeaderFacts>,
+}
+
+pub(crate) struct HeaderFacts {
+    pub(crate) leaf_idx: u128,
+    pub(crate) path_len: usize,
+    pub(crate) rlp: Result<Vec<u8>, String>,
+}
+
+fn validate<M, H>(input: &MmrJson<M, H>, mmr: MmrFacts) -> Vec<FieldError> {
+    let mut errors = vec![];
+    if mmr.size == 0 {
+        errors.push(FieldError::new("meta.size", "mmr is empty"));
+    }
+    if mmr.peaks == 0 {
+        errors.push(FieldError::new("meta.peaks", "no peaks"));
+    }
+    if mmr.headers.is_empty() {
+        errors.push(FieldError::new("headers", "no headers"));
+    }
+
+    let mut blocks = HashSet::new();
+    for (index, header) in mmr.headers.iter().enumerate() {
+        if header.leaf_idx == 0 || header.leaf_idx > mmr.size {
+            errors.push(FieldError::new(
+                format!("headers[{index}].proof.leaf_idx"),
+                format!("{} is not in an mmr of size {}", header.leaf_idx, mmr.size),
+            ));
+        }
+        let decoded = header
+            .rlp
+            .as_ref()
+            .map_err(|err| err.to_string())
+            .and_then(|rlp| decode_header(rlp).map_err(|err| err.to_string()));
+        match decoded {
+            Ok(decoded) => {
+                blocks.insert(decoded.number);
+            }
+            Err(err) => errors.push(FieldError::new(
+                format!("headers[{index}].rlp"),
+                format!("not an rlp encoded header: {err}"),
+            )),
+        }
+    }
+    let mut check_block = |field: String, block_number: u64| {
+        if !blocks.contains(&block_number) {
+            errors.push(FieldError::new(
+                field,
+                format!("no header for block {block_number}"),
+            ));
+        }
+    };
+
+    let mut account_blocks = HashSet::new();
+    for (index, account) in input.accounts.iter().enumerate() {
+        for (proof_index, proof) in account.proofs.iter().enumerate() {
+            check_block(
+                format!("accounts[{index}].proofs[{proof_index}].block_number"),
+                proof.block_number,
+            );
+            account_blocks.insert((account.address, proof.block_number));
+        }
+    }
+    for (index, diff) in input.diffs.iter().enumerate() {
+        check_block(format!("diffs[{index}].from_block"), diff.from_block);
+        check_block(format!("diffs[{index}].to_block"), diff.to_block);
+    }
+
+    for (index, account) in input.accounts.iter().enumerate() {
+        if !account
+            .account_key
+            .eq_ignore_ascii_case(&hex::encode(keccak256(account.address)))
+        {
+            errors.push(FieldError::new(
+                format!("accounts[{index}].account_key"),
+                format!("is not the keccak of {}", account.address),
+            ));
+        }
+    }
+    for (index, storage) in input.storages.iter().enumerate() {
+        if storage.storage_key != keccak256(storage.slot) {
+            errors.push(FieldError::new(
+                format!("storages[{index}].storage_key"),
+                format!("is not the keccak of slot {}", storage.slot),
+            ));
+        }
+        for (proof_index, proof) in storage.proofs.iter().enumerate() {
+            if !account_blocks.contains(&(storage.address, proof.block_number)) {
+                errors.push(FieldError::new(
+                    format!("storages[{index}].proofs[{proof_index}].block_number"),
+                    format!(
+                        "no proof of account {} at block {}",
+                        storage.address, proof.block_number
+                    ),
+                ));
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../keccak-test.json")).unwrap()
+    }
+
+    fn errors(input: Value) -> Vec<String> {
+        match Input::parse(&input.to_string()) {
+            Ok(_) => vec![],
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let input = Input::parse(&fixture().to_string()).ok().unwrap();
+        assert_eq!(input.hash_id(), HashId::Keccak);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut input = fixture();
+        input["accounts"][0]["proofs"][0]["block_number"] = json!("one");
+        assert!(errors(input)[0].starts_with(
+            "`accounts[0].proofs[0].block_number`: invalid type: string \"one\", expected u64"
+        ));
+
+        let mut input = fixture();
+        input["hash_id"] = json!("sha256");
+        assert!(errors(input)[0].starts_with("`hash_id`: unknown variant `sha256`"));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut input = fixture();
+        input["headers"][0]["proof"]["leaf_idx"] = json!(0);
+        input["accounts"][0]["proofs"][0]["block_number"] = json!(1);
+        input["storages"][0]["storage_key"] = input["accounts"][0]["account_key"].clone();
+        // keys are unprefixed hex, as `hex::encode` writes them
+        input["accounts"][0]["account_key"] = json!(format!(
+            "0x{}",
+            input["accounts"][0]["account_key"].as_str().unwrap()
+        ));
+        assert_eq!(
+            errors(input),
+            [
+                "`headers[0].proof.leaf_idx`: 0 is not in an mmr of size 13024091",
+                "`accounts[0].proofs[0].block_number`: no header for block 1",
+                "`accounts[0].account_key`: is not the keccak of \
+                 0x75CeC1db9dCeb703200EAa6595f66885C962B920",
+                "`storages[0].storage_key`: is not the keccak of slot \
+                 0x0000000000000000000000000000000000000000000000000000000000000001",
+                "`storages[0].proofs[0].block_number`: no proof of account \
+                 0x75CeC1db9dCeb703200EAa6595f66885C962B920 at block 5641516",
+            ]
+        );
+    }
+}
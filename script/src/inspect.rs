@@ -0,0 +1,427 @@
+//! A readable view of an input: the decoded headers, accounts and slots, and where each
+//! header sits in the MMR. Anything the guest would reject is listed as an issue.
+
+use std::{collections::HashMap, fmt};
+
+use alloy_primitives::Address;
+use hdp_lib::{
+    eip1186::{verify_claims, Eip1186Error},
+    hash::HashId,
+    json::MmrJson,
+    mmr, mmr_keccak,
+    mmr_keccak::{get_peak_info, mmr_size_to_leaf_count},
+    mpt_cached::CachedVerifier,
+    rlp::decode_header,
+};
+
+use crate::input::{Input, MmrFacts};
+
+pub struct Inspection {
+    pub lines: Vec<String>,
+    pub issues: Vec<String>,
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        if self.issues.is_empty() {
+            return write!(f, "no issues");
+        }
+        write!(f, "{} issues:", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What only hashing the MMR shows, left out of [`Input::validate`].
+struct MmrHashes {
+    /// Whether the peaks bag to the root.
+    root_bags: bool,
+    /// Whether each header is the leaf its proof points at; `None` if the size is not that
+    /// of an mmr, which the verifier does not expect.
+    in_mmr: Option<Vec<Result<bool, String>>>,
+}
+
+/// Decodes every part of `input`, with the problems [`Input::validate`] finds and those
+/// only the hashes and proofs show.
+pub fn inspect(input: &Input) -> Inspection {
+    let issues = input.validate().iter().map(ToString::to_string).collect();
+    let mmr = input.facts();
+    let leaves = leaf_count(mmr.size);
+    match input {
+        Input::Keccak(json) => {
+            let hashes = MmrHashes {
+                root_bags: json
+                    .meta
+                    .bag_peaks()
+                    .is_ok_and(|root| root == json.meta.root),
+                in_mmr: leaves.map(|_| {
+                    json.headers
+                        .iter()
+                        .map(|header| {
+                            mmr_keccak::verify_header(&json.meta, header)
+                                .map_err(|err| err.to_string())
+                        })
+                        .collect()
+                }),
+            };
+            inspect_json(json, input.hash_id(), mmr, leaves, hashes, issues)
+        }
+        Input::Poseidon(json) => {
+            let hashes = MmrHashes {
+                root_bags: json
+                    .meta
+                    .bag_peaks()
+                    .is_ok_and(|root| root == json.meta.root),
+                in_mmr: leaves.map(|_| {
+                    json.headers
+                        .iter()
+                        .map(|header| {
+                            mmr::verify_header(&json.meta, header).map_err(|err| err.to_string())
+                        })
+                        .collect()
+                }),
+            };
+            inspect_json(json, input.hash_id(), mmr, leaves, hashes, issues)
+        }
+    }
+}
+
+/// Leaves of an mmr of `size` elements, or `None` if no mmr has that size. Sizes over
+/// `usize::MAX / 2` are rejected first, as counting their leaves would overflow.
+fn leaf_count(size: u128) -> Option<usize> {
+    if size == 0 {
+        return Some(0);
+    }
+    if size > (usize::MAX / 2) as u128 {
+        return None;
+    }
+    let leaves = mmr_size_to_leaf_count(size as usize);
+    (2 * leaves - leaves.count_ones() as usize == size as usize).then_some(leaves)
+}
+
+fn inspect_json<M, H>(
+    input: &MmrJson<M, H>,
+    hash_id: HashId,
+    mmr: MmrFacts,
+    leaves: Option<usize>,
+    hashes: MmrHashes,
+    mut issues: Vec<String>,
+) -> Inspection {
+    let mut lines = vec![];
+
+    let hasher = format!("{hash_id:?}").to_lowercase();
+    match leaves {
+        Some(leaves) => {
+            let heights: Vec<u32> = (0..usize::BITS)
+                .rev()
+                .filter(|height| leaves & (1 << height) != 0)
+                .collect();
+            lines.push(format!(
+                "mmr: {hasher}, size {}, {leaves} leaves, peak heights {heights:?}",
+                mmr.size
+            ));
+            if mmr.peaks != heights.len() {
+                issues.push(format!(
+                    "`meta.peaks`: {} peaks, an mmr of {leaves} leaves has {}",
+                    mmr.peaks,
+                    heights.len()
+                ));
+            }
+        }
+        None => {
+            lines.push(format!("mmr: {hasher}, size {}", mmr.size));
+            issues.push(format!(
+                "`meta.size`: {} is not the size of an mmr",
+                mmr.size
+            ));
+        }
+    }
+    if !hashes.root_bags {
+        issues.push("`meta.root`: is not the bagging of the peaks".to_string());
+    }
+
+    let mut state_roots = HashMap::new();
+    for (index, header) in mmr.headers.iter().enumerate() {
+        lines.push(format!("headers[{index}]:"));
+        match header
+            .rlp
+            .as_ref()
+            .ok()
+            .and_then(|rlp| decode_header(rlp).ok())
+        {
+            Some(decoded) => {
+                lines.push(format!(
+                    "  block {}, hash {}",
+                    decoded.number,
+                    decoded.hash_slow()
+                ));
+                lines.push(format!(
+                    "  state root {}, timestamp {}",
+                    decoded.state_root, decoded.timestamp
+                ));
+                state_roots.insert(decoded.number, decoded.state_root);
+            }
+            None => lines.push("  undecodable rlp".to_string()),
+        }
+
+        let leaf_idx = header.leaf_idx;
+        if leaves.is_some() && (1..=mmr.size).contains(&leaf_idx) {
+            let (peak, height) = get_peak_info(mmr.size as usize, leaf_idx as usize);
+            lines.push(format!(
+                "  element {leaf_idx} under peak {peak} of height {height}, path of {}",
+                header.path_len
+            ));
+            if header.path_len != height {
+                issues.push(format!(
+                    "`headers[{index}].proof.mmr_path`: {} nodes, peak {peak} has height {height}",
+                    header.path_len
+                ));
+            }
+        }
+        match hashes.in_mmr.as_ref().map(|in_mmr| &in_mmr[index]) {
+            Some(Ok(true)) => lines.push("  in mmr".to_string()),
+            Some(Ok(false)) => issues.push(format!(
+                "`headers[{index}]`: not the leaf its proof points at"
+            )),
+            // a root that does not bag is already an issue, and fails every header
+            Some(Err(_)) if !hashes.root_bags => {}
+            Some(Err(err)) => issues.push(format!("`headers[{index}].proof`: {err}")),
+            // neither is a size that is not an mmr's
+            None => {}
+        }
+    }
+
+    // one verifier per trie, so nodes shared between proofs are hashed once
+    let mut state_tries: HashMap<u64, CachedVerifier> = state_roots
+        .into_iter()
+        .map(|(block, state_root)| (block, CachedVerifier::new(state_root)))
+        .collect();
+    let mut storage_tries: HashMap<(Address, u64), CachedVerifier> = HashMap::new();
+    for (index, account) in input.accounts.iter().enumerate() {
+        lines.push(format!("accounts[{index}]: {}", account.address));
+        for (proof_index, proof) in account.proofs.iter().enumerate() {
+            let block = proof.block_number;
+            let Some(state_trie) = state_tries.get_mut(&block) else {
+                lines.push(format!("  block {block}: no header"));
+                continue;
+            };
+            match state_trie.account(account.address, &proof.proof) {
+                Ok(Some(proven)) => {
+                    lines.push(format!(
+                        "  block {block}: nonce {}, balance {}",
+                        proven.nonce, proven.balance
+                    ));
+                    lines.push(format!(
+                        "    storage root {}, code hash {}",
+                        proven.storage_root, proven.code_hash
+                    ));
+                    storage_tries.insert(
+                        (account.address, block),
+                        CachedVerifier::new(proven.storage_root),
+                    );
+                }
+                Ok(None) => {
+                    lines.push(format!("  block {block}: absent"));
+                    issues.push(format!(
+                        "`accounts[{index}].proofs[{proof_index}]`: account is absent at \
+                         block {block}"
+                    ));
+                    continue;
+                }
+                Err(err) => {
+                    lines.push(format!("  block {block}: invalid proof"));
+                    issues.push(format!("`accounts[{index}].proofs[{proof_index}]`: {err}"));
+                    continue;
+                }
+            }
+            if account.claim_at(block).is_some() {
+                if let Err(Eip1186Error::AccountClaimMismatch(_)) =
+                    verify_claims(state_trie.root(), block, account, &[])
+                {
+                    issues.push(format!(
+                        "`accounts[{index}].claims`: do not match the proof at block {block}"
+                    ));
+                }
+            }
+        }
+    }
+
+    for (index, storage) in input.storages.iter().enumerate() {
+        lines.push(format!(
+            "storages[{index}]: {} slot {}",
+            storage.address, storage.slot
+        ));
+        for (proof_index, proof) in storage.proofs.iter().enumerate() {
+            let block = proof.block_number;
+            let Some(storage_trie) = storage_tries.get_mut(&(storage.address, block)) else {
+                lines.push(format!("  block {block}: no proven account"));
+                continue;
+            };
+            match storage_trie.storage(storage.slot, &proof.proof) {
+                Ok(value) => {
+                    lines.push(format!("  block {block}: {value} ({value:#x})"));
+                    match storage.claim_at(block) {
+                        Some(claim) if claim.value != value => issues.push(format!(
+                            "`storages[{index}].claims`: claim {} at block {block}, the proof \
+                             holds {value}",
+                            claim.value
+                        )),
+                        _ => {}
+                    }
+                }
+                Err(err) => {
+                    lines.push(format!("  block {block}: invalid proof"));
+                    issues.push(format!("`storages[{index}].proofs[{proof_index}]`: {err}"));
+                }
+            }
+        }
+    }
+
+    Inspection { lines, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{keccak256, B256};
+    use hdp_lib::{
+        fixture::{FixtureAccount, StateBuilder},
+        rlp::encode_header,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn inspection(input: Value) -> Inspection {
+        inspect(&Input::from_json(&input.to_string()).ok().unwrap())
+    }
+
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../keccak-test.json")).unwrap()
+    }
+
+    #[test]
+    fn test_inspect_fixture() {
+        let inspection = inspection(fixture());
+        assert!(inspection.issues.is_empty(), "{inspection}");
+        for line in [
+            "mmr: keccak, size 13024091, 6512052 leaves, \
+             peak heights [22, 21, 17, 16, 14, 12, 11, 10, 8, 7, 5, 4, 2]",
+            "  block 5641516, hash \
+             0x75a0075713d3a55e62abf8479a178b1e230ca0eab1a97b30bdefd195ea0a22b8",
+            "  element 610913 under peak 0 of height 22, path of 22",
+            "  block 5641516: nonce 1, balance 20000000000000",
+            "  block 5641516: 20000000000000 (0x12309ce54000)",
+        ] {
+            assert!(
+                inspection.lines.iter().any(|printed| printed == line),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inspect_claims_and_root() {
+        let mut input = fixture();
+        input["meta"]["peaks"][0] = input["meta"]["peaks"][1].clone();
+        input["accounts"][0]["claims"] = json!([{
+            "block_number": 5641516,
+            "nonce": 2,
+            "balance": "0x12309ce54000",
+            "storage_root": "0x69bbf0407f9d5438512c6218768a9581f377fa5dc119ea1409b917b75c242e1c",
+            "code_hash": "0xeab3448e22d0f75e09ed849b2e87ac6739db4104db4eaeeffcc66cfa819755fd",
+        }]);
+        input["storages"][0]["claims"] = json!([{ "block_number": 5641516, "value": "0x2a" }]);
+        assert_eq!(
+            inspection(input).issues,
+            [
+                "`meta.root`: is not the bagging of the peaks",
+                "`accounts[0].claims`: do not match the proof at block 5641516",
+                "`storages[0].claims`: claim 42 at block 5641516, the proof holds 20000000000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inspect_mmr_path() {
+        let mut input = fixture();
+        input["headers"][0]["proof"]["mmr_path"]
+            .as_array_mut()
+            .unwrap()
+            .pop();
+        input["accounts"][0]["proofs"][0]["block_number"] = json!(1);
+        let inspection = inspection(input);
+        assert_eq!(
+            inspection.issues,
+            [
+                "`accounts[0].proofs[0].block_number`: no header for block 1",
+                "`storages[0].proofs[0].block_number`: no proof of account \
+                 0x75CeC1db9dCeb703200EAa6595f66885C962B920 at block 5641516",
+                "`headers[0].proof.mmr_path`: 21 nodes, peak 0 has height 22",
+                "`headers[0]`: not the leaf its proof points at",
+            ]
+        );
+        assert!(inspection.to_string().ends_with(
+            "4 issues:\n  `accounts[0].proofs[0].block_number`: no header for block 1\n  \
+             `storages[0].proofs[0].block_number`: no proof of account \
+             0x75CeC1db9dCeb703200EAa6595f66885C962B920 at block 5641516\n  \
+             `headers[0].proof.mmr_path`: 21 nodes, peak 0 has height 22\n  \
+             `headers[0]`: not the leaf its proof points at"
+        ));
+    }
+
+    #[test]
+    fn test_inspect_absent_account() {
+        // a single-leaf mmr of a state without the account
+        let state = StateBuilder::new()
+            .account(Address::with_last_byte(1), FixtureAccount::default())
+            .build(7);
+        let rlp = encode_header(&state.header);
+        let mut meta = mmr_keccak::MmrMeta::new(B256::ZERO, 1, vec![keccak256(&rlp)]);
+        meta.root = meta.bag_peaks().unwrap();
+        let proof = mmr_keccak::HeaderInclusionProof {
+            leaf_idx: 1,
+            mmr_path: vec![],
+        };
+        let input = Input::Keccak(MmrJson {
+            hash_id: HashId::Keccak,
+            meta,
+            headers: vec![mmr_keccak::Header::from_rlp_bytes(&rlp, proof)],
+            accounts: vec![state.account_proof(Address::with_last_byte(2))],
+            storages: vec![],
+            diffs: vec![],
+        });
+
+        let inspection = inspect(&input);
+        assert!(inspection
+            .lines
+            .iter()
+            .any(|line| line == "  block 7: absent"));
+        assert_eq!(
+            inspection.issues,
+            ["`accounts[0].proofs[0]`: account is absent at block 7"]
+        );
+    }
+
+    #[test]
+    fn test_inspect_oversized_mmr() {
+        let mut input = fixture();
+        input["meta"]["size"] = json!(u64::MAX);
+        let inspection = inspection(input);
+        assert!(inspection
+            .lines
+            .iter()
+            .any(|line| line == "mmr: keccak, size 18446744073709551615"));
+        assert_eq!(
+            inspection.issues,
+            [
+                "`meta.size`: 18446744073709551615 is not the size of an mmr",
+                "`meta.root`: is not the bagging of the peaks",
+            ]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use hdp_lib::hash::HashId;
+
+pub mod artifact;
+pub mod bench;
+pub mod cycles;
+pub mod fetch;
+pub mod input;
+pub mod inspect;
+#[cfg(any(test, feature = "mock-rpc"))]
+pub mod mock_rpc;
+pub mod rpc;
+pub mod synthetic;
+
+/// The ELF (executable and linkable format) files for the Succinct RISC-V zkVM, one per MMR
+/// hasher. Both are built by `build.rs`.
+pub const KECCAK_MMR_ELF: &[u8] = include_bytes!("../../elf/keccak-mmr-elf");
+pub const POSEIDON_MMR_ELF: &[u8] = include_bytes!("../../elf/poseidon-mmr-elf");
+
+/// The ELF of the program built for `hash_id`.
+pub fn elf(hash_id: HashId) -> &'static [u8] {
+    match hash_id {
+        HashId::Keccak => KECCAK_MMR_ELF,
+        HashId::Poseidon => POSEIDON_MMR_ELF,
+    }
+}
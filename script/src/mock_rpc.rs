@@ -0,0 +1,118 @@
+//! A JSON-RPC server on localhost replaying recorded [`Exchange`]s, so fetching can be
+//! tested without a node. It answers one request per connection and anything it has no
+//! recording for with a JSON-RPC error.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use serde_json::{json, Value};
+
+use crate::rpc::Exchange;
+
+pub struct MockRpcServer {
+    address: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockRpcServer {
+    /// Starts serving `exchanges` on a free port.
+    pub fn start(exchanges: Vec<Exchange>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    // a client hanging up early only loses its own answer
+                    let _ = serve(stream, &exchanges);
+                }
+            }
+        });
+
+        Ok(Self {
+            address,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+impl Drop for MockRpcServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake the listener up so it sees `stop`
+        let _ = TcpStream::connect(self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, exchanges: &[Exchange]) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Ok(request) => answer(&request, exchanges),
+        Err(err) => error(Value::Null, -32700, err.to_string()),
+    }
+    .to_string();
+    write!(
+        &stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{response}",
+        response.len()
+    )?;
+    (&stream).flush()
+}
+
+fn answer(request: &Value, exchanges: &[Exchange]) -> Value {
+    let id = request["id"].clone();
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = &request["params"];
+    match exchanges
+        .iter()
+        .find(|exchange| exchange.method == method && exchange.params == *params)
+    {
+        Some(exchange) => json!({ "jsonrpc": "2.0", "id": id, "result": exchange.result }),
+        None => error(
+            id,
+            -32601,
+            format!("no recorded response to {method} {params}"),
+        ),
+    }
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
@@ -0,0 +1,163 @@
+//! A blocking JSON-RPC client for the two calls fixtures need, able to record what it
+//! fetched so [`crate::mock_rpc`] can replay it offline.
+
+use std::{fs, io, path::Path};
+
+use alloy_primitives::{Address, B256};
+use alloy_rpc_types_eth::{EIP1186AccountProofResponse, Header};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    #[error("{method}: {source}")]
+    Http {
+        method: String,
+        source: reqwest::Error,
+    },
+
+    #[error("{method}: rpc error {code}: {message}")]
+    Rpc {
+        method: String,
+        code: i64,
+        message: String,
+    },
+
+    #[error("{method}: unexpected response: {source}")]
+    Response {
+        method: String,
+        source: serde_json::Error,
+    },
+
+    #[error("block {0} not found")]
+    BlockNotFound(u64),
+
+    #[error("{0}: {1}")]
+    Io(String, io::Error),
+}
+
+/// A call and the result it got, as recorded and replayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Exchange {
+    pub method: String,
+    pub params: Value,
+    pub result: Value,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+}
+
+pub struct RpcClient {
+    url: String,
+    http: reqwest::blocking::Client,
+    next_id: u64,
+    recording: Option<Vec<Exchange>>,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::blocking::Client::new(),
+            next_id: 1,
+            recording: None,
+        }
+    }
+
+    /// Keeps every successful call, see [`RpcClient::save_recording`].
+    pub fn recording(mut self) -> Self {
+        self.recording = Some(vec![]);
+        self
+    }
+
+    /// The header of block `number`; the other fields of the block are ignored.
+    pub fn header(&mut self, number: u64) -> Result<Header, RpcError> {
+        let (method, params) = block_params(number);
+        self.call::<Option<Header>>(method, params)?
+            .ok_or(RpcError::BlockNotFound(number))
+    }
+
+    /// `eth_getProof` of `address` and its `slots` at block `number`.
+    pub fn proof(
+        &mut self,
+        address: Address,
+        slots: &[B256],
+        number: u64,
+    ) -> Result<EIP1186AccountProofResponse, RpcError> {
+        let (method, params) = proof_params(address, slots, number);
+        self.call(method, params)
+    }
+
+    pub fn save_recording(&self, path: &Path) -> Result<(), RpcError> {
+        let exchanges = self.recording.as_deref().unwrap_or_default();
+        let json = serde_json::to_string_pretty(exchanges).map_err(io::Error::other);
+        json.and_then(|json| fs::write(path, json))
+            .map_err(|err| RpcError::Io(path.display().to_string(), err))
+    }
+
+    fn call<T: DeserializeOwned>(&mut self, method: &str, params: Value) -> Result<T, RpcError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id,
+            "method": method,
+            "params": params,
+        });
+        self.next_id += 1;
+
+        let http_error = |source| RpcError::Http {
+            method: method.to_string(),
+            source,
+        };
+        let response: Response = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(http_error)?;
+        if let Some(error) = response.error {
+            return Err(RpcError::Rpc {
+                method: method.to_string(),
+                code: error.code,
+                message: error.message,
+            });
+        }
+
+        let result = response.result.unwrap_or_default();
+        if let Some(recording) = &mut self.recording {
+            recording.push(Exchange {
+                method: method.to_string(),
+                params,
+                result: result.clone(),
+            });
+        }
+        serde_json::from_value(result).map_err(|source| RpcError::Response {
+            method: method.to_string(),
+            source,
+        })
+    }
+}
+
+pub(crate) fn block_params(number: u64) -> (&'static str, Value) {
+    (
+        "eth_getBlockByNumber",
+        json!([format!("{number:#x}"), false]),
+    )
+}
+
+pub(crate) fn proof_params(address: Address, slots: &[B256], number: u64) -> (&'static str, Value) {
+    (
+        "eth_getProof",
+        json!([address, slots, format!("{number:#x}")]),
+    )
+}
@@ -0,0 +1,388 @@
+//! Synthetic inputs of any size, for benchmarking the guest without a node.
+//!
+//! Leaf `i` of the MMR stands for block `i`. The last `blocks` leaves are real headers,
+//! chained by parent hash, each committing to a state of `accounts` accounts holding
+//! `slots` slots. Every account and slot is proven at every block.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use hdp_lib::{
+    account::HdpAccount,
+    chunks::bytes_to_felts,
+    fixture::{FixtureAccount, StateBuilder},
+    hash::{Hash, HashId, Keccak256, StarkPoseidoen},
+    json::MmrJson,
+    mmr, mmr_keccak,
+    rlp::encode_header,
+    storage::HdpStorage,
+};
+use starknet_types_core::{
+    felt::Felt,
+    hash::{Poseidon, StarkHash},
+};
+
+use crate::input::Input;
+
+/// Shape of a synthetic input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::Args)]
+pub struct Spec {
+    /// Headers to prove, the last leaves of the MMR.
+    #[clap(long, default_value = "1")]
+    pub blocks: usize,
+
+    /// Leaves of the MMR, at least `blocks`.
+    #[clap(long, default_value = "1024")]
+    pub mmr_leaves: usize,
+
+    /// Accounts proven at every block.
+    #[clap(long, default_value = "1")]
+    pub accounts: usize,
+
+    /// Slots proven for every account at every block.
+    #[clap(long, default_value = "1")]
+    pub slots: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SpecError {
+    #[error("at least one block is needed")]
+    NoBlocks,
+
+    #[error("{blocks} blocks do not fit in an mmr of {mmr_leaves} leaves")]
+    TooFewLeaves { blocks: usize, mmr_leaves: usize },
+}
+
+impl Spec {
+    pub fn validate(&self) -> Result<(), SpecError> {
+        if self.blocks == 0 {
+            return Err(SpecError::NoBlocks);
+        }
+        if self.mmr_leaves < self.blocks {
+            return Err(SpecError::TooFewLeaves {
+                blocks: self.blocks,
+                mmr_leaves: self.mmr_leaves,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn account_proofs(&self) -> usize {
+        self.blocks * self.accounts
+    }
+
+    pub fn storage_proofs(&self) -> usize {
+        self.account_proofs() * self.slots
+    }
+}
+
+/// Generates a valid input of shape `spec` for the MMR hashed with `hash_id`.
+pub fn generate(spec: &Spec, hash_id: HashId) -> Result<Input, SpecError> {
+    spec.validate()?;
+
+    let first_block = (spec.mmr_leaves - spec.blocks) as u64;
+    let addresses: Vec<Address> = (1..=spec.accounts as u64)
+        .map(|i| Address::left_padding_from(&i.to_be_bytes()))
+        .collect();
+    let slots: Vec<B256> = (0..spec.slots as u64)
+        .map(|i| U256::from(i).into())
+        .collect();
+
+    let mut headers = vec![];
+    let mut accounts: BTreeMap<Address, HdpAccount> = BTreeMap::new();
+    let mut storages: BTreeMap<(Address, B256), HdpStorage> = BTreeMap::new();
+    let mut parent_hash = B256::ZERO;
+    for block_number in first_block..first_block + spec.blocks as u64 {
+        let mut state = StateBuilder::new();
+        for (i, address) in addresses.iter().enumerate() {
+            let value = U256::from(block_number) + U256::from(i);
+            state = state.account(
+                *address,
+                FixtureAccount {
+                    nonce: block_number,
+                    balance: value,
+                    storage: slots
+                        .iter()
+                        .map(|slot| (*slot, value + U256::from(1)))
+                        .collect(),
+                    ..Default::default()
+                },
+            );
+        }
+        let mut state = state.build(block_number);
+        state.header.parent_hash = parent_hash;
+        parent_hash = state.header.hash_slow();
+        headers.push(encode_header(&state.header));
+
+        for address in &addresses {
+            let proof = state.account_proof(*address);
+            match accounts.get_mut(address) {
+                Some(account) => {
+                    account.proofs.extend(proof.proofs);
+                    account.claims.extend(proof.claims);
+                }
+                None => {
+                    accounts.insert(*address, proof);
+                }
+            }
+            for slot in &slots {
+                let proof = state.storage_proof(*address, *slot);
+                match storages.get_mut(&(*address, *slot)) {
+                    Some(storage) => {
+                        storage.proofs.extend(proof.proofs);
+                        storage.claims.extend(proof.claims);
+                    }
+                    None => {
+                        storages.insert((*address, *slot), proof);
+                    }
+                }
+            }
+        }
+    }
+    let accounts = accounts.into_values().collect();
+    let storages = storages.into_values().collect();
+
+    Ok(match hash_id {
+        HashId::Keccak => {
+            let mmr = Mmr::<Keccak256>::build(spec.mmr_leaves, &headers);
+            Input::Keccak(MmrJson {
+                hash_id,
+                meta: mmr_keccak::MmrMeta::new(mmr.root, mmr.size, mmr.peaks),
+                headers: headers
+                    .iter()
+                    .zip(mmr.paths)
+                    .map(|(rlp, (leaf_idx, mmr_path))| {
+                        let proof = mmr_keccak::HeaderInclusionProof { leaf_idx, mmr_path };
+                        mmr_keccak::Header::from_rlp_bytes(rlp, proof)
+                    })
+                    .collect(),
+                accounts,
+                storages,
+                diffs: vec![],
+            })
+        }
+        HashId::Poseidon => {
+            let mmr = Mmr::<StarkPoseidoen>::build(spec.mmr_leaves, &headers);
+            Input::Poseidon(MmrJson {
+                hash_id,
+                meta: mmr::MmrMeta::new(mmr.root, mmr.size, mmr.peaks),
+                headers: headers
+                    .iter()
+                    .zip(mmr.paths)
+                    .map(|(rlp, (leaf_idx, mmr_path))| {
+                        let proof = mmr::HeaderInclusionProof { leaf_idx, mmr_path };
+                        mmr::Header::from_rlp_bytes(rlp, proof)
+                    })
+                    .collect(),
+                accounts,
+                storages,
+                diffs: vec![],
+            })
+        }
+    })
+}
+
+/// How the guest hashes MMR nodes, leaves and size.
+trait MmrHasher {
+    type Node: Copy;
+
+    fn node(left: Self::Node, right: Self::Node) -> Self::Node;
+
+    fn leaf(rlp: &[u8]) -> Self::Node;
+
+    fn size(size: u128) -> Self::Node;
+}
+
+impl MmrHasher for Keccak256 {
+    type Node = B256;
+
+    fn node(left: B256, right: B256) -> B256 {
+        Keccak256::hash(left, right)
+    }
+
+    fn leaf(rlp: &[u8]) -> B256 {
+        keccak256(rlp)
+    }
+
+    fn size(size: u128) -> B256 {
+        U256::from(size).into()
+    }
+}
+
+impl MmrHasher for StarkPoseidoen {
+    type Node = Felt;
+
+    fn node(left: Felt, right: Felt) -> Felt {
+        Poseidon::hash(&left, &right)
+    }
+
+    fn leaf(rlp: &[u8]) -> Felt {
+        Poseidon::hash_array(&bytes_to_felts(rlp))
+    }
+
+    fn size(size: u128) -> Felt {
+        Felt::from(size)
+    }
+}
+
+/// An MMR and the inclusion proofs of its last leaves.
+struct Mmr<H: MmrHasher> {
+    size: u128,
+    root: H::Node,
+    peaks: Vec<H::Node>,
+    /// Element index and path of each proven leaf.
+    paths: Vec<(u128, Vec<H::Node>)>,
+}
+
+impl<H: MmrHasher> Mmr<H> {
+    /// An MMR of `leaves` leaves ending with the headers `rlps`. The leaves before them
+    /// are filler, the hash of their block number.
+    fn build(leaves: usize, rlps: &[Vec<u8>]) -> Self {
+        let first = leaves - rlps.len();
+        let leaves: Vec<H::Node> = (0..first as u64)
+            .map(|block_number| H::leaf(&block_number.to_be_bytes()))
+            .chain(rlps.iter().map(|rlp| H::leaf(rlp)))
+            .collect();
+
+        // Mountains are perfect trees, largest first, one per bit of the leaf count.
+        let mut peaks = vec![];
+        let mut paths = vec![];
+        let mut start = 0;
+        for height in (0..usize::BITS).rev() {
+            let width = 1 << height;
+            if leaves.len() & width == 0 {
+                continue;
+            }
+            let mut levels = vec![leaves[start..start + width].to_vec()];
+            while levels.last().unwrap().len() > 1 {
+                let level = levels.last().unwrap();
+                levels.push(
+                    level
+                        .chunks(2)
+                        .map(|pair| H::node(pair[0], pair[1]))
+                        .collect(),
+                );
+            }
+            peaks.push(levels.last().unwrap()[0]);
+
+            for leaf in first.max(start)..start + width {
+                let mut index = leaf - start;
+                let path = levels[..levels.len() - 1]
+                    .iter()
+                    .map(|level| {
+                        let sibling = level[index ^ 1];
+                        index /= 2;
+                        sibling
+                    })
+                    .collect();
+                paths.push((element_index(leaf), path));
+            }
+            start += width;
+        }
+
+        let size = 2 * leaves.len() as u128 - leaves.len().count_ones() as u128;
+        let bagged = peaks
+            .iter()
+            .rev()
+            .copied()
+            .reduce(|right, left| H::node(left, right))
+            .unwrap();
+        Self {
+            size,
+            root: H::node(H::size(size), bagged),
+            peaks,
+            paths,
+        }
+    }
+}
+
+/// 1-based position in the MMR of the 0-based `leaf`.
+fn element_index(leaf: usize) -> u128 {
+    2 * leaf as u128 - leaf.count_ones() as u128 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use hdp_lib::verify::verify_all;
+
+    use super::*;
+
+    const SPEC: Spec = Spec {
+        blocks: 3,
+        mmr_leaves: 11,
+        accounts: 2,
+        slots: 2,
+    };
+
+    #[test]
+    fn test_element_index() {
+        // leaves of the mmr 1 2 (3) 4 5 (6) (7) 8
+        let indices: Vec<_> = (0..5).map(element_index).collect();
+        assert_eq!(indices, [1, 2, 4, 5, 8]);
+    }
+
+    #[test]
+    fn test_keccak_input_is_valid() {
+        let Input::Keccak(input) = generate(&SPEC, HashId::Keccak).unwrap() else {
+            panic!("not a keccak input");
+        };
+        assert_eq!(input.meta.size, 19);
+        assert_eq!(input.meta.peaks.len(), 3);
+        assert_eq!(input.headers.len(), 3);
+        assert_eq!(input.accounts.len(), 2);
+        assert_eq!(input.storages.len(), 4);
+        assert_eq!(input.storages[0].proofs.len(), 3);
+
+        let public_values = verify_all(&input);
+        assert!(
+            public_values.outcome.is_valid(),
+            "{:?}",
+            public_values.outcome
+        );
+    }
+
+    #[test]
+    fn test_poseidon_headers_are_in_mmr() {
+        let Input::Poseidon(input) = generate(&SPEC, HashId::Poseidon).unwrap() else {
+            panic!("not a poseidon input");
+        };
+        assert_eq!(input.hash_id, HashId::Poseidon);
+        for header in &input.headers {
+            assert!(mmr::verify_header(&input.meta, header).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_single_leaf_mmr() {
+        let spec = Spec {
+            blocks: 1,
+            mmr_leaves: 1,
+            accounts: 0,
+            slots: 0,
+        };
+        let Input::Keccak(input) = generate(&spec, HashId::Keccak).unwrap() else {
+            panic!("not a keccak input");
+        };
+        assert_eq!(input.meta.size, 1);
+        assert!(input.headers[0].proof.mmr_path.is_empty());
+        assert!(verify_all(&input).outcome.is_valid());
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        let no_blocks = Spec { blocks: 0, ..SPEC };
+        assert!(matches!(
+            generate(&no_blocks, HashId::Keccak),
+            Err(SpecError::NoBlocks)
+        ));
+
+        let too_many_blocks = Spec { blocks: 12, ..SPEC };
+        assert!(matches!(
+            generate(&too_many_blocks, HashId::Poseidon),
+            Err(SpecError::TooFewLeaves {
+                blocks: 12,
+                mmr_leaves: 11
+            })
+        ));
+    }
+}